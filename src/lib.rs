@@ -1,38 +1,107 @@
 //! This crate provides a macro called [`include_toml!`] which parses properties of `Cargo.toml` at compile time.
+//!
+//! The macros that look up an index accept the `first`, `out_dir` and `member` modifiers of [`include_toml!`]
+//! before their input to read another file instead, e.g. a configuration file of the crate:
+//!
+//! ```rust
+//! use include_cargo_toml2::include_toml_as;
+//!
+//! let port = include_toml_as!(first ["tests/fixtures/config.toml"], "server"."port", u16);
+//! assert_eq!(port, 8080);
+//! ```
+//!
+//! Exceptions are `include_toml_source!`, [`include_toml_merged!`] and [`include_toml_diff!`],
+//! which read the files they are given, and the macros reading fixed fields of `[package]`,
+//! like [`include_toml_about!`] and [`include_toml_author_email!`].
 
 extern crate proc_macro;
 extern crate proc_macro2;
 extern crate quote;
+#[cfg(feature = "include_toml_regex")]
+extern crate regex;
 extern crate syn;
 extern crate toml;
+#[cfg(feature = "include_toml_source")]
+extern crate toml_edit;
 
+#[cfg(feature = "include_toml_regex")]
+use crate::regex::Regex;
+#[cfg(feature = "include_toml_source")]
+use crate::toml_edit::{DocumentMut, Item, Value as EditValue};
 use crate::{
     proc_macro::TokenStream,
     proc_macro2::{Literal, Span as Span2, TokenStream as TokenStream2},
     quote::{quote, ToTokens},
     syn::{
-        parse::{Parse, ParseBuffer},
+        braced, bracketed,
+        ext::IdentExt,
+        parenthesized,
+        parse::{Nothing, Parse, ParseBuffer},
         parse_macro_input,
-        token::Dot,
-        Error as SynError, Lit, LitBool,
+        punctuated::Punctuated,
+        token::{Bracket, Colon, Comma, Dot, Dot2, DotDotEq, Eq, Or, Question, Sub},
+        Error as SynError, Expr, Ident, Lit, LitBool, LitInt, LitStr, Path as SynPath, Type,
+    },
+    toml::{
+        value::{Date, Datetime, Offset, Time},
+        Table, Value,
     },
-    toml::Value,
 };
+use std::convert::TryFrom;
 use std::env::var;
-use std::fs::read_to_string;
+use std::fmt::{Debug, Display};
+use std::fs::{read_dir, read_to_string};
 use std::path::{Path, PathBuf};
 
 /// Helper that stores either integer or string.
 ///
 /// Used to create vector of indexing items in [`TomlIndex`].
+/// The span of the originating literal is kept, so errors point at the failing item.
+#[derive(Clone)]
 enum Index {
-    Int(usize),
-    Str(String),
+    Int(usize, Span2),
+    Str(String, Span2),
+    /// First element of an array, written as `first`.
+    First(Span2),
+    /// Last element of an array, written as `last`.
+    Last(Span2),
+    /// Only table of an array whose key equals the value, written as `[key = value]`.
+    Where(String, Value, Span2),
+    /// Item followed by a help message in brackets, which is added to errors of the item.
+    Help(Box<Index>, String),
+}
+
+impl Index {
+    /// Returns the key if this item is a string.
+    fn key(&self) -> Option<&str> {
+        match self {
+            Index::Int(..) | Index::First(_) | Index::Last(_) | Index::Where(..) => None,
+            Index::Str(key, _) => Some(key),
+            Index::Help(item, _) => item.key(),
+        }
+    }
+}
+
+impl Display for Index {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Index::Int(index, _) => write!(f, "{}", index),
+            Index::Str(key, _) => write!(f, "{}", key),
+            Index::First(_) => write!(f, "first"),
+            Index::Last(_) => write!(f, "last"),
+            Index::Where(key, expected, _) => write!(f, "[{} = {}]", key, expected),
+            Index::Help(item, _) => write!(f, "{}", item),
+        }
+    }
 }
 
 /// Struct that parses input of [`include_toml`].
 ///
-/// Input should consist of either string literals or integers separated by dots.
+/// Input should consist of either string literals, integers or `first` and `last` separated by dots.
+/// Adjacent string literals are joined to a single key.
+/// Each item may be followed by a help message in brackets, like `"key"["help"]`.
+/// An array of tables may be followed by `[key = value]` in brackets, selecting the only table whose key equals the value.
+#[derive(Clone)]
 struct TomlIndex(Vec<Index>);
 
 impl Parse for TomlIndex {
@@ -40,25 +109,61 @@ impl Parse for TomlIndex {
         let mut another_one = true;
         let mut index = Vec::new();
         while another_one {
-            index.push(match input.parse::<Lit>() {
-                Ok(lit) => match lit {
-                    Lit::Str(lit_str) => Index::Str(lit_str.value()),
-                    Lit::Int(lit_int) => Index::Int(
-                        lit_int
-                            .base10_digits()
-                            .parse()
-                            .expect("Cannot parse literal integer"),
-                    ),
-                    _ => return Err(SynError::new(input.span(), "Unsupported literal")),
-                },
-                Err(e) => {
-                    return Err(SynError::new(
-                        input.span(),
-                        format!("Cannot parse index item: {}", e),
-                    ))
+            let item = if input.peek(Ident) {
+                let ident: Ident = input.parse()?;
+                match ident.to_string().as_str() {
+                    "first" => Index::First(ident.span()),
+                    "last" => Index::Last(ident.span()),
+                    _ => {
+                        return Err(SynError::new(
+                            ident.span(),
+                            format!("Unknown index `{}`, expected `first` or `last`", ident),
+                        ))
+                    }
                 }
-            });
-            if let Err(_) = input.parse::<Dot>() {
+            } else {
+                match input.parse::<Lit>() {
+                    Ok(lit) => match lit {
+                        Lit::Str(lit_str) => {
+                            let mut key = lit_str.value();
+                            while input.peek(LitStr) {
+                                key.push_str(&input.parse::<LitStr>()?.value());
+                            }
+                            Index::Str(key, lit_str.span())
+                        }
+                        Lit::Int(lit_int) => Index::Int(lit_int.base10_parse()?, lit_int.span()),
+                        other => return Err(SynError::new(other.span(), "Unsupported literal")),
+                    },
+                    Err(e) => {
+                        return Err(SynError::new(
+                            input.span(),
+                            format!("Cannot parse index item: {}", e),
+                        ))
+                    }
+                }
+            };
+            index.push(item);
+            while input.peek(Bracket) {
+                let content;
+                bracketed!(content in input);
+                if content.peek2(Eq) {
+                    let key = if content.peek(LitStr) {
+                        content.parse::<LitStr>()?.value()
+                    } else {
+                        content.parse::<Ident>()?.to_string()
+                    };
+                    let span = content.parse::<Eq>()?.span;
+                    let expected = lit_value(&content.parse()?)?;
+                    index.push(Index::Where(key, expected, span));
+                } else {
+                    let item = index.pop().expect("Index contains item");
+                    index.push(Index::Help(
+                        Box::new(item),
+                        content.parse::<LitStr>()?.value(),
+                    ));
+                }
+            }
+            if input.parse::<Dot>().is_err() {
                 another_one = false;
             }
         }
@@ -66,34 +171,367 @@ impl Parse for TomlIndex {
     }
 }
 
+/// Default of [`Options::max_depth`].
+const MAX_DEPTH: usize = 128;
+
+/// Settings for [`translate_with`].
+struct Options {
+    /// Emit numeric literals without type suffix, so their type is inferred from context.
+    raw: bool,
+    /// Maximum nesting of arrays and tables, to not overflow the stack of the compiler.
+    max_depth: usize,
+    /// Emit non-empty arrays whose elements have the same type as Rust arrays instead of tuples.
+    arrays: bool,
+    /// Representation of datetimes.
+    datetimes: Datetimes,
+}
+
+/// Representation of datetimes selected by the `dt` modifier.
+enum Datetimes {
+    /// [`&str`] in RFC 3339 format.
+    String,
+    /// Tuple of the components, see [`datetime_tuple`].
+    Tuple,
+    /// Seconds since the Unix epoch as [`i64`], only for offset datetimes.
+    Timestamp,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            raw: false,
+            max_depth: MAX_DEPTH,
+            arrays: false,
+            datetimes: Datetimes::String,
+        }
+    }
+}
+
 /// Converts any TOML value to valid Rust types.
-fn translate(input: Value) -> TokenStream2 {
-    match input {
-        Value::String(s) => Lit::new(Literal::string(&s)).to_token_stream().into(),
-        Value::Integer(i) => Lit::new(Literal::i64_suffixed(i)).to_token_stream().into(),
-        Value::Float(f) => Lit::new(Literal::f64_suffixed(f)).to_token_stream().into(),
-        Value::Datetime(d) => Lit::new(Literal::string(&d.to_string()))
-            .to_token_stream()
-            .into(),
-        Value::Boolean(b) => Lit::Bool(LitBool::new(b, Span2::call_site()))
-            .to_token_stream()
-            .into(),
+fn translate(input: Value) -> Result<TokenStream2, SynError> {
+    translate_with(input, &Options::default())
+}
+
+/// Converts any TOML value to valid Rust types, as configured by `options`.
+fn translate_with(input: Value, options: &Options) -> Result<TokenStream2, SynError> {
+    translate_nested(input, options, 0)
+}
+
+/// Converts a TOML value nested `depth` levels deep, failing if it exceeds [`Options::max_depth`].
+fn translate_nested(
+    input: Value,
+    options: &Options,
+    depth: usize,
+) -> Result<TokenStream2, SynError> {
+    if depth > options.max_depth {
+        return Err(error(format!(
+            "Value is nested deeper than {} levels",
+            options.max_depth
+        )));
+    }
+    Ok(match input {
+        Value::String(s) => Lit::new(Literal::string(&s)).to_token_stream(),
+        Value::Integer(i) if options.raw => Lit::new(Literal::i64_unsuffixed(i)).to_token_stream(),
+        Value::Integer(i) => Lit::new(Literal::i64_suffixed(i)).to_token_stream(),
+        Value::Float(f) if !f.is_finite() => {
+            return Err(error(format!("Value {:?} is out of range for f64", f)))
+        }
+        Value::Float(f) if options.raw => Lit::new(Literal::f64_unsuffixed(f)).to_token_stream(),
+        Value::Float(f) => Lit::new(Literal::f64_suffixed(f)).to_token_stream(),
+        Value::Datetime(d) => match options.datetimes {
+            Datetimes::String => Lit::new(Literal::string(&d.to_string())).to_token_stream(),
+            Datetimes::Tuple => datetime_tuple(&d),
+            Datetimes::Timestamp => Literal::i64_suffixed(timestamp(&d)?).to_token_stream(),
+        },
+        Value::Boolean(b) => Lit::Bool(LitBool::new(b, Span2::call_site())).to_token_stream(),
         Value::Array(a) => {
+            let array = options.arrays && homogeneous(&a);
             let mut ts = TokenStream2::new();
             for value in a {
-                let v = translate(value);
+                let v = translate_nested(value, options, depth + 1)?;
                 ts.extend(quote! (#v,));
             }
-            quote! ((#ts))
+            if array {
+                quote! ([#ts])
+            } else {
+                quote! ((#ts))
+            }
         }
         Value::Table(t) => {
             let mut ts = TokenStream2::new();
             for (key, value) in t {
-                let v = translate(value);
-                ts.extend(quote! ((#key, #v)));
+                let v = translate_nested(value, options, depth + 1)?;
+                ts.extend(quote! ((#key, #v),));
             }
             quote! ((#ts))
         }
+    })
+}
+
+/// Returns whether `array` is not empty and all elements have the same Rust type.
+fn homogeneous(array: &[Value]) -> bool {
+    match array.split_first() {
+        Some((first, rest)) => {
+            let ty = rust_type(first).to_string();
+            rest.iter().all(|value| rust_type(value).to_string() == ty)
+        }
+        None => false,
+    }
+}
+
+/// Returns `array_mode` of `[package.metadata.include-cargo-toml2]` in `manifest`, which defaults to `false`.
+fn array_mode(manifest: &Value) -> Result<bool, SynError> {
+    let mode = manifest
+        .get("package")
+        .and_then(|package| package.get("metadata"))
+        .and_then(|metadata| metadata.get("include-cargo-toml2"))
+        .and_then(|settings| settings.get("array_mode"));
+    match mode {
+        None => Ok(false),
+        Some(Value::Boolean(b)) => Ok(*b),
+        Some(other) => Err(error(format!(
+            "Expected boolean for array_mode, found {}",
+            other.type_str()
+        ))),
+    }
+}
+
+/// TOML document the value is looked up in.
+enum Source {
+    /// `Cargo.toml` of the crate that is currently compiled.
+    CargoToml,
+    /// First file out of the given paths that can be read and parsed.
+    First(Vec<String>),
+    /// File at the given path relative to `OUT_DIR`, where build scripts write generated files.
+    OutDir(String),
+    /// `Cargo.toml` of the workspace member with the given package name.
+    Member(String),
+}
+
+impl Source {
+    /// Parses the arguments of the source modifier `modifier`, or returns `None` for other modifiers.
+    fn parse_modifier(modifier: &Ident, input: &ParseBuffer) -> Result<Option<Self>, SynError> {
+        let source = match modifier.to_string().as_str() {
+            "first" => {
+                let content;
+                bracketed!(content in input);
+                let paths = Punctuated::<LitStr, Comma>::parse_terminated(&content)?;
+                Source::First(paths.iter().map(LitStr::value).collect())
+            }
+            "out_dir" => Source::OutDir(input.parse::<LitStr>()?.value()),
+            "member" => Source::Member(input.parse::<LitStr>()?.value()),
+            _ => return Ok(None),
+        };
+        input.parse::<Comma>()?;
+        Ok(Some(source))
+    }
+
+    /// Parses an optional leading source modifier, defaulting to [`Source::CargoToml`].
+    fn parse_optional(input: &ParseBuffer) -> Result<Self, SynError> {
+        if input.peek(Ident) && (input.peek2(Bracket) || input.peek2(LitStr)) {
+            let modifier: Ident = input.fork().parse()?;
+            if ["first", "out_dir", "member"].contains(&modifier.to_string().as_str()) {
+                input.parse::<Ident>()?;
+                return Ok(Source::parse_modifier(&modifier, input)?.expect("Source modifier"));
+            }
+        }
+        Ok(Source::CargoToml)
+    }
+
+    /// Reads and parses the document.
+    fn load(&self) -> Result<Document, SynError> {
+        match self {
+            Source::CargoToml => Ok(Document {
+                value: cargo_toml()?,
+                file: "Cargo.toml".to_string(),
+            }),
+            Source::First(paths) => first(&manifest_dir(), paths),
+            Source::OutDir(path) => out_dir(var("OUT_DIR").ok(), path),
+            Source::Member(name) => Ok(Document {
+                value: member(&manifest_dir(), name)?,
+                file: format!("Cargo.toml of member \"{}\"", name),
+            }),
+        }
+    }
+}
+
+/// Document read from a [`Source`], together with the name of its file for error messages.
+struct Document {
+    value: Value,
+    file: String,
+}
+
+impl Document {
+    /// Runs [`lookup_in`] on the document.
+    fn lookup(&self, index: TomlIndex) -> Result<Value, SynError> {
+        lookup_in(index, &self.value, &self.file)
+    }
+
+    /// Runs [`lookup_opt`] on the document.
+    fn lookup_opt(&self, index: TomlIndex) -> Result<Option<Value>, SynError> {
+        lookup_opt(index, &self.value, &self.file)
+    }
+}
+
+/// Struct that parses input `I` preceded by an optional source modifier like for [`IncludeInput`].
+struct Sourced<I> {
+    source: Source,
+    input: I,
+}
+
+impl<I: Parse> Parse for Sourced<I> {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let source = Source::parse_optional(input)?;
+        let input = input.parse()?;
+        Ok(Self { source, input })
+    }
+}
+
+/// Struct that parses input of [`include_toml`].
+///
+/// Input should consist of optional modifiers followed by a [`TomlIndex`] and optional [`Transform`]s:
+///
+/// - `first [<path>, ...],`, `out_dir <path>,` and `member <name>,` select the [`Source`].
+/// - `raw` sets [`Options::raw`].
+/// - `array` and `tuple` set [`Options::arrays`].
+/// - `dt=<representation>` sets [`Options::datetimes`].
+/// - `cfg(<predicate>) ?` makes the index a [`Condition`] branch.
+/// - `| <transform>` after the index appends to the transforms.
+struct IncludeInput {
+    source: Source,
+    options: Options,
+    /// Set by the `array` and `tuple` modifiers, which take precedence over [`array_mode`].
+    arrays: Option<bool>,
+    index: TomlIndex,
+    condition: Option<Condition>,
+    transforms: Vec<Transform>,
+}
+
+/// Transformation of a string value, applied after the lookup.
+enum Transform {
+    Trim,
+    Upper,
+    Lower,
+    Unquote,
+}
+
+impl Transform {
+    /// Parses the name of a transform.
+    fn parse(name: &Ident) -> Result<Self, SynError> {
+        match name.to_string().as_str() {
+            "trim" => Ok(Transform::Trim),
+            "upper" => Ok(Transform::Upper),
+            "lower" => Ok(Transform::Lower),
+            "unquote" => Ok(Transform::Unquote),
+            _ => Err(SynError::new(
+                name.span(),
+                format!(
+                    "Unknown transform `{}`, expected `trim`, `upper`, `lower` or `unquote`",
+                    name
+                ),
+            )),
+        }
+    }
+
+    /// Applies the transform to `s`.
+    fn apply(&self, s: &str) -> String {
+        match self {
+            Transform::Trim => s.trim().to_string(),
+            Transform::Upper => s.to_uppercase(),
+            Transform::Lower => s.to_lowercase(),
+            Transform::Unquote => unquote(s).to_string(),
+        }
+    }
+}
+
+/// Applies `transforms` in order to the string `value`, or returns `value` unchanged if there are none.
+fn transform(transforms: &[Transform], value: Value) -> Result<Value, SynError> {
+    if transforms.is_empty() {
+        return Ok(value);
+    }
+    let s = string(value).map_err(|e| error(format!("Cannot transform value: {}", e)))?;
+    Ok(Value::String(
+        transforms
+            .iter()
+            .fold(s, |s, transform| transform.apply(&s)),
+    ))
+}
+
+/// Alternative index that is selected if `predicate` does not hold for the compiled crate.
+struct Condition {
+    predicate: TokenStream2,
+    otherwise: TomlIndex,
+}
+
+impl Parse for IncludeInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let mut source = Source::CargoToml;
+        let mut options = Options::default();
+        let mut arrays = None;
+        let mut predicate = None;
+        while predicate.is_none() && input.peek(Ident) {
+            let modifier: Ident = input.parse()?;
+            if let Some(selected) = Source::parse_modifier(&modifier, input)? {
+                source = selected;
+                continue;
+            }
+            match modifier.to_string().as_str() {
+                "raw" => options.raw = true,
+                "array" => arrays = Some(true),
+                "tuple" => arrays = Some(false),
+                "dt" => {
+                    input.parse::<Eq>()?;
+                    let mode: Ident = input.parse()?;
+                    options.datetimes = match mode.to_string().as_str() {
+                        "string" => Datetimes::String,
+                        "tuple" => Datetimes::Tuple,
+                        "timestamp" => Datetimes::Timestamp,
+                        _ => {
+                            return Err(SynError::new(
+                                mode.span(),
+                                format!("Unknown datetime representation `{}`", mode),
+                            ))
+                        }
+                    };
+                }
+                "cfg" => {
+                    let content;
+                    parenthesized!(content in input);
+                    predicate = Some(content.parse::<TokenStream2>()?);
+                    input.parse::<Question>()?;
+                }
+                _ => {
+                    return Err(SynError::new(
+                        modifier.span(),
+                        format!("Unknown modifier `{}`", modifier),
+                    ))
+                }
+            }
+        }
+        let index = input.parse()?;
+        let condition = match predicate {
+            Some(predicate) => {
+                input.parse::<Colon>()?;
+                let otherwise = input.parse()?;
+                Some(Condition {
+                    predicate,
+                    otherwise,
+                })
+            }
+            None => None,
+        };
+        let mut transforms = Vec::new();
+        while input.parse::<Or>().is_ok() {
+            transforms.push(Transform::parse(&input.parse()?)?);
+        }
+        Ok(Self {
+            source,
+            options,
+            arrays,
+            index,
+            condition,
+            transforms,
+        })
     }
 }
 
@@ -101,15 +539,25 @@ fn translate(input: Value) -> TokenStream2 {
 ///
 /// # TOML to Rust conversion
 ///
-/// - TOML [string](Value::String) -> Rust [`&str`]
+/// - TOML [string](Value::String) -> Rust [`&'static str`](str)
 /// - TOML [integer](Value::Integer) -> Rust [`i64`]
 /// - TOML [float](Value::Float) -> Rust [`f64`]
 /// - TOML [boolean](Value::Boolean) -> Rust [`bool`]
-/// - TOML [datetime](Value::Datetime) -> Rust [`&str`]
+/// - TOML [datetime](Value::Datetime) -> Rust [`&'static str`](str)
 /// - TOML [array](Value::Array) -> Rust tuple \
-///     TOML arrays can hold different types, Rust [`Vec`]s can't.
-/// - TOML [table](Value::Table) -> Rust tuple \
-///     TOML tables can hold different types, Rust [`Vec`]s can't.
+///   TOML arrays can hold different types, Rust [`Vec`]s can't.
+/// - TOML [table](Value::Table) -> Rust tuple of `(key, value)` tuples \
+///   TOML tables can hold different types, Rust [`Vec`]s can't.
+///
+/// Strings are emitted as string literals, so they live for `'static` and can initialize statics:
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// static NAME: &str = include_toml!("package"."name");
+/// let version: &'static str = include_toml!("package"."version");
+/// assert_eq!((NAME, version), ("include-cargo-toml2", "0.3.1"));
+/// ```
 ///
 /// # Example
 ///
@@ -137,6 +585,9 @@ fn translate(input: Value) -> TokenStream2 {
 /// );
 /// ```
 ///
+/// The Rust type depends on the manifest content, not on the index.
+/// For example, `"profile"."release"."opt-level"` is an [`i64`] for `opt-level = 3`, but a [`&str`] for `opt-level = "s"`.
+///
 /// Because TOML's arrays and tables do not work like [`Vec`] and [`HashMap`](std::collections::HashMap), tuples are used.
 ///
 /// ```rust
@@ -148,93 +599,7003 @@ fn translate(input: Value) -> TokenStream2 {
 /// );
 /// ```
 ///
+/// Adjacent string literals are joined to a single key, which helps `macro_rules!` wrappers to assemble keys:
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// assert_eq!(
+///     include_toml!("pack" "age"."version"),
+///     "0.3.1"
+/// );
+/// ```
+///
+/// A key or integer can be followed by a help message in brackets, which is shown if looking it up fails:
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml;
+///
+/// // error: Cannot find key "myapp" in Cargo.toml
+/// //        help: run `myapp init` to create this
+/// let this_fails = include_toml!("package"."metadata"."myapp"["run `myapp init` to create this"]."key");
+/// ```
+///
+/// `first` and `last` refer to the first and last element of an array:
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// assert_eq!(include_toml!("package"."keywords".first), "macro");
+/// assert_eq!(include_toml!("package"."keywords".last), "parse");
+/// ```
+///
+/// An array of tables can be followed by `[key = value]`, which selects the only table whose key equals the value,
+/// like [`include_toml_find!`]:
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// // path of [[bin]] with name = "mytool"
+/// let path = include_toml!(first ["tests/fixtures/tables.toml"], "bin"[name = "mytool"]."path");
+/// assert_eq!(path, "src/mytool.rs");
+/// ```
+///
 /// Leading or trailing dots are not allowed:
 ///
 /// ```rust,compile_fail
-/// use include_cargo_toml::include_toml;
+/// use include_cargo_toml2::include_toml;
 ///
 /// let this_fails = include_toml!(."package"."name");
 /// let this_fails_too = include_toml!("package"."name".);
 /// ```
+///
+/// # Reading other files
+///
+/// With the `first` modifier, the value is looked up in the first file that can be read and parsed.
+/// Paths are relative to the directory containing `Cargo.toml`:
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// let port = include_toml!(first ["tests/fixtures/config.local.toml", "tests/fixtures/config.toml"], "server"."port");
+/// assert_eq!(port, 3000);
+/// ```
+///
+/// With the `out_dir` modifier, the value is looked up in a file relative to `OUT_DIR`,
+/// e.g. one written by the build script:
+///
+/// ```rust,ignore
+/// use include_cargo_toml2::include_toml;
+///
+/// let generated = include_toml!(out_dir "generated.toml", "x"."y");
+/// ```
+///
+/// With the `member` modifier, the value is looked up in `Cargo.toml` of another member of the workspace,
+/// which is found by its package name:
+///
+/// ```rust,ignore
+/// use include_cargo_toml2::include_toml;
+///
+/// let version = include_toml!(member "other-crate", "package"."version");
+/// ```
+///
+/// # Unsuffixed numbers
+///
+/// Integers and floats are emitted as [`i64`] and [`f64`] by default.
+/// With the `raw` modifier, they are emitted without type suffix and adapt to the context:
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// let port: u16 = include_toml!(raw first ["tests/fixtures/config.toml"], "server"."port");
+/// assert_eq!(port, 8080);
+/// ```
+///
+/// This also allows to use integers as const generic arguments of any integer type:
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// let buffer = [0u8; include_toml!(raw first ["tests/fixtures/config.toml"], "server"."port")];
+/// assert_eq!(buffer.len(), 8080);
+/// ```
+///
+/// # Arrays
+///
+/// With the `array` modifier, non-empty TOML arrays whose elements have the same Rust type
+/// are emitted as Rust arrays instead of tuples:
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// let keywords: [&str; 5] = include_toml!(array "package"."keywords");
+/// assert!(keywords.contains(&"macro"));
+/// ```
+///
+/// To enable this for all invocations of `include_toml!` and [`include_toml_rev!`] in a crate,
+/// set `array_mode` in its `Cargo.toml`:
+///
+/// ```toml
+/// [package.metadata.include-cargo-toml2]
+/// array_mode = true
+/// ```
+///
+/// The `array` and `tuple` modifiers take precedence over `array_mode`,
+/// so `tuple` emits tuples even if `array_mode` is enabled.
+/// Other macros are not affected by `array_mode` and emit arrays as tuples.
+///
+/// # Datetimes
+///
+/// Datetimes are emitted as [`&str`] by default. The `dt` modifier selects another representation:
+///
+/// - `dt=string`: [`&str`] in RFC 3339 format
+/// - `dt=tuple`: tuple of the components, like `((1979u16, 5u8, 27u8), (7u8, 32u8, 0u8, 0u32), 0i16)`
+///   for an offset datetime with the offset in minutes; local datetimes omit the offset,
+///   local dates and times are `(year, month, day)` and `(hour, minute, second, nanosecond)`
+/// - `dt=timestamp`: seconds since the Unix epoch as [`i64`], only for offset datetimes
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// // [package.metadata]
+/// // built = 1979-05-27T07:32:00Z
+/// let (date, time, offset) = include_toml!(dt=tuple first ["tests/fixtures/metadata.toml"], "package"."metadata"."built");
+/// assert_eq!((date, time, offset), ((1979, 5, 27), (7, 32, 0, 0), 0));
+/// let timestamp: i64 = include_toml!(dt=timestamp first ["tests/fixtures/metadata.toml"], "package"."metadata"."built");
+/// assert_eq!(timestamp, 296638320);
+/// ```
+///
+/// # Transforms
+///
+/// Strings can be transformed at compile time by appending `| <transform>` after the index,
+/// where transforms are applied from left to right:
+///
+/// - `trim` removes leading and trailing whitespace
+/// - `upper` and `lower` convert to uppercase and lowercase
+/// - `unquote` removes one layer of surrounding double quotes, like [`include_toml_unquote!`]
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// assert_eq!(include_toml!("package"."name" | upper | trim), "INCLUDE-CARGO-TOML2");
+/// ```
+///
+/// # Conditional selection
+///
+/// `cfg(<predicate>) ? <index> : <index>` selects the first index if the `cfg` predicate holds for the crate
+/// that calls the macro and the second one otherwise.
+/// Any predicate accepted by `#[cfg]` can be used, and both values may have different types.
+/// Both indexes have to exist in `Cargo.toml`, regardless of which one is selected.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml;
+///
+/// let value = include_toml!(cfg(target_os = "none") ? "package"."version" : "package"."name");
+/// assert_eq!(value, "include-cargo-toml2");
+/// ```
 #[proc_macro]
 pub fn include_toml(input: TokenStream) -> TokenStream {
-    let dir = var("CARGO_MANIFEST_DIR").expect("Environment variable CARGO_MANIFEST_DIR not set!");
-    let path = Path::new(&dir).join("Cargo.toml");
-
-    let cargo_toml = parse(&path);
-    let index: TomlIndex = parse_macro_input!(input);
-    let result = lookup(index, cargo_toml);
+    let IncludeInput {
+        source,
+        mut options,
+        arrays,
+        index,
+        condition,
+        transforms,
+    } = parse_macro_input!(input);
 
-    translate(result).into()
+    arrays
+        .map_or_else(|| cargo_toml().and_then(|toml| array_mode(&toml)), Ok)
+        .and_then(|arrays| {
+            options.arrays = arrays;
+            source.load()
+        })
+        .and_then(|document| {
+            let select =
+                |index| translate_with(transform(&transforms, document.lookup(index)?)?, &options);
+            let selected = select(index)?;
+            match condition {
+                Some(Condition {
+                    predicate,
+                    otherwise,
+                }) => {
+                    let otherwise = select(otherwise)?;
+                    Ok(conditional(&predicate, selected, otherwise))
+                }
+                None => Ok(selected),
+            }
+        })
+        .unwrap_or_else(SynError::into_compile_error)
+        .into()
 }
 
-fn parse(path: &PathBuf) -> Value {
-    let content = read_to_string(path).expect("Cannot read Cargo.toml");
-    content.parse::<Value>().expect("Cannot parse Cargo.toml to json")
+/// Struct that parses input of [`include_toml_expect`].
+///
+/// Input should consist of a [`TomlIndex`] followed by a comma and a string literal.
+struct ExpectInput {
+    index: TomlIndex,
+    message: LitStr,
 }
 
-fn lookup(index: TomlIndex, mut toml: Value) -> Value {
-    for item in index.0 {
-        match item {
-            Index::Int(index) => {
-                toml = toml[index].clone();
-            }
-            Index::Str(index) => {
-                toml = toml[index].clone();
-            }
-        }
+impl Parse for ExpectInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        input.parse::<Comma>()?;
+        let message = input.parse()?;
+        Ok(Self { index, message })
     }
-    toml
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::{lookup, parse};
-    use std::env::var;
-    use std::path::Path;
-    use toml::Value;
+/// Parse `Cargo.toml` at compile time, failing with a custom message.
+///
+/// Works like [`include_toml!`], but if the value cannot be found, the `compile_error!` shows the given message
+/// in front of the default one, and still points at the failing part of the index.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_expect;
+///
+/// assert_eq!(
+///     include_toml_expect!("package"."version", "version must be set"),
+///     "0.3.1"
+/// );
+/// ```
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_expect;
+///
+/// // error: version must be set
+/// let this_fails = include_toml_expect!("package"."versio", "version must be set");
+/// ```
+#[proc_macro]
+pub fn include_toml_expect(input: TokenStream) -> TokenStream {
+    expand_with(input, |ExpectInput { index, message }, document| {
+        translate(expect(index, &message, &document)?)
+    })
+}
 
-    #[test]
-    fn should_parse_when_cargo_toml_is_valid() {
-        let dir = var("CARGO_MANIFEST_DIR").expect("Environment variable CARGO_MANIFEST_DIR must be set!");
+/// Parse a single character from `Cargo.toml` at compile time.
+///
+/// The selected value must be a string consisting of exactly one Unicode scalar value,
+/// which is emitted as a [`char`] literal.
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_char;
+///
+/// // error: Expected a single character, found 19 characters
+/// let this_fails = include_toml_char!("package"."name");
+/// ```
+#[proc_macro]
+pub fn include_toml_char(input: TokenStream) -> TokenStream {
+    expand(input, to_char)
+}
 
-        let path = Path::new(&dir).join("Cargo.toml");
-        println!("{}", dir);
-        let toml = parse(&path);
+/// Parses input as [`TomlIndex`], looks up the value in the document and converts it.
+///
+/// Errors of either step are emitted as `compile_error!`.
+fn expand<F>(input: TokenStream, convert: F) -> TokenStream
+where
+    F: FnOnce(Value) -> Result<TokenStream2, SynError>,
+{
+    expand_with(input, |index: TomlIndex, document| {
+        document.lookup(index).and_then(convert)
+    })
+}
 
-        assert_eq!("include-cargo-toml2", toml["package"]["name"].as_str().unwrap());
-    }
+/// Parses input as `I` after an optional source modifier and passes it to `convert` together with the document,
+/// which is `Cargo.toml` unless the modifier selects another file.
+///
+/// Errors of either step are emitted as `compile_error!`.
+fn expand_with<I, F>(input: TokenStream, convert: F) -> TokenStream
+where
+    I: Parse,
+    F: FnOnce(I, Document) -> Result<TokenStream2, SynError>,
+{
+    let Sourced { source, input } = parse_macro_input!(input as Sourced<I>);
 
-    #[test]
-    fn should_fetch_attribute_when_cargo_toml_is_given() {
-        let cargo_toml = r#"
-        [package]
-        edition="2021"
-        version="0.1.0"
-        "#;
+    source
+        .load()
+        .and_then(|document| convert(input, document))
+        .unwrap_or_else(SynError::into_compile_error)
+        .into()
+}
 
-        let toml: Value = cargo_toml.parse::<Value>().expect("Cannot parse Cargo.toml");
-        let index = syn::parse_str(r#""package"."version""#).unwrap();
+/// Creates an error pointing at the macro invocation.
+fn error<T: Display>(message: T) -> SynError {
+    SynError::new(Span2::call_site(), message)
+}
 
-        let result = lookup(index, toml);
+/// Returns the string held by `value`, or an error naming the actual type.
+fn string(value: Value) -> Result<String, SynError> {
+    match value {
+        Value::String(s) => Ok(s),
+        other => Err(error(format!(
+            "Expected string, found {}",
+            other.type_str()
+        ))),
+    }
+}
 
-        assert_eq!("0.1.0", result.as_str().unwrap());
+/// Converts a single-character string to a [`char`] literal.
+fn to_char(value: Value) -> Result<TokenStream2, SynError> {
+    let s = string(value)?;
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Lit::new(Literal::character(c)).to_token_stream()),
+        _ => Err(error(format!(
+            "Expected a single character, found {} characters",
+            s.chars().count()
+        ))),
+    }
+}
+
+/// Returns the directory containing `Cargo.toml` of the crate that is currently compiled.
+fn manifest_dir() -> PathBuf {
+    let dir = var("CARGO_MANIFEST_DIR").expect("Environment variable CARGO_MANIFEST_DIR not set!");
+    PathBuf::from(dir)
+}
+
+/// Reads `Cargo.toml` of the crate that is currently compiled.
+fn cargo_toml() -> Result<Value, SynError> {
+    parse(&manifest_dir().join("Cargo.toml"))
+}
+
+fn parse(path: &Path) -> Result<Value, SynError> {
+    toml::from_str(&read(path)?)
+        .map_err(|e| error(format!("Cannot parse {}: {}", file_name(path), e)))
+}
+
+/// Reads `path`, naming the file in the error.
+fn read(path: &Path) -> Result<String, SynError> {
+    read_to_string(path).map_err(|e| error(format!("Cannot read {}: {}", file_name(path), e)))
+}
+
+/// Returns the name of the file at `path` for use in error messages.
+fn file_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => path.display().to_string(),
+    }
+}
+
+/// Parses `Cargo.toml` of the member named `name` of the workspace containing `dir`.
+fn member(dir: &Path, name: &str) -> Result<Value, SynError> {
+    let root = workspace_root(dir)?;
+    let workspace = parse(&root.join("Cargo.toml"))?;
+    let patterns = workspace["workspace"]
+        .get("members")
+        .cloned()
+        .map(strings)
+        .transpose()?
+        .unwrap_or_default();
+    for pattern in patterns {
+        for member_dir in member_dirs(&root, &pattern) {
+            let manifest = parse(&member_dir.join("Cargo.toml"))?;
+            let package_name = manifest.get("package").and_then(|p| p.get("name"));
+            if package_name.and_then(Value::as_str) == Some(name) {
+                return Ok(manifest);
+            }
+        }
+    }
+    Err(error(format!("Cannot find workspace member \"{}\"", name)))
+}
+
+/// Returns the closest directory above or at `dir` whose `Cargo.toml` has a `[workspace]` table.
+fn workspace_root(dir: &Path) -> Result<PathBuf, SynError> {
+    for ancestor in dir.ancestors() {
+        let manifest = ancestor.join("Cargo.toml");
+        if manifest.is_file() && parse(&manifest)?.get("workspace").is_some() {
+            return Ok(ancestor.to_path_buf());
+        }
+    }
+    Err(error("Cannot find workspace containing Cargo.toml"))
+}
+
+/// Returns the directories below `root` matching the member `pattern`, where `*` matches any part of a name.
+fn member_dirs(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![root.to_path_buf()];
+    for component in pattern.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        dirs = dirs
+            .into_iter()
+            .flat_map(|dir| {
+                if !component.contains('*') {
+                    return vec![dir.join(component)];
+                }
+                let mut matches: Vec<PathBuf> = read_dir(&dir)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.path().is_dir())
+                    .filter(|entry| wildcard(component, &entry.file_name().to_string_lossy()))
+                    .map(|entry| entry.path())
+                    .collect();
+                matches.sort();
+                matches
+            })
+            .collect();
+    }
+    dirs.into_iter()
+        .filter(|dir| dir.join("Cargo.toml").is_file())
+        .collect()
+}
+
+/// Returns whether `name` matches `pattern`, where `*` matches any sequence of characters.
+fn wildcard(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            name.starts_with(prefix)
+                && (0..=name.len() - prefix.len())
+                    .filter(|&i| name.is_char_boundary(prefix.len() + i))
+                    .any(|i| wildcard(rest, &name[prefix.len() + i..]))
+        }
+    }
+}
+
+/// Parses `path` relative to `dir`, which is the value of `OUT_DIR` if set by Cargo.
+fn out_dir(dir: Option<String>, path: &str) -> Result<Document, SynError> {
+    let dir =
+        dir.ok_or_else(|| error("Environment variable OUT_DIR not set, is there a build script?"))?;
+    let path = Path::new(&dir).join(path);
+    Ok(Document {
+        value: parse(&path)?,
+        file: file_name(&path),
+    })
+}
+
+/// Parses the first of `paths` relative to `dir` that can be read and parsed.
+fn first(dir: &Path, paths: &[String]) -> Result<Document, SynError> {
+    paths
+        .iter()
+        .map(|path| dir.join(path))
+        .find_map(|path| {
+            Some(Document {
+                value: parse(&path).ok()?,
+                file: file_name(&path),
+            })
+        })
+        .ok_or_else(|| error(format!("Cannot read any of {:?}", paths)))
+}
+
+/// Returns the value at `index` of `Cargo.toml`, cloning only the selected value instead of every level.
+fn lookup(index: TomlIndex, toml: &Value) -> Result<Value, SynError> {
+    lookup_in(index, toml, "Cargo.toml")
+}
+
+/// Runs [`lookup`] on the document `toml` read from `file`, which is named in errors.
+fn lookup_in(index: TomlIndex, toml: &Value, file: &str) -> Result<Value, SynError> {
+    let mut value = toml;
+    for item in &index.0 {
+        value = get(value, item, file)?;
+    }
+    Ok(value.clone())
+}
+
+/// Runs [`lookup_in`], returning `None` instead of an error if a key is missing.
+///
+/// Other errors, like indexing into a scalar, are still returned.
+fn lookup_opt(index: TomlIndex, toml: &Value, file: &str) -> Result<Option<Value>, SynError> {
+    let mut value = toml;
+    for item in &index.0 {
+        if is_missing(value, item) {
+            return Ok(None);
+        }
+        value = get(value, item, file)?;
+    }
+    Ok(Some(value.clone()))
+}
+
+/// Returns whether `item` is a key that is missing from the table `toml`.
+fn is_missing(toml: &Value, item: &Index) -> bool {
+    match (item, toml) {
+        (Index::Help(item, _), toml) => is_missing(toml, item),
+        (Index::Str(key, _), Value::Table(table)) => !table.contains_key(key),
+        _ => false,
+    }
+}
+
+/// Returns the value at `item` of `toml`, or an error pointing at `item` that names `file`.
+fn get<'a>(toml: &'a Value, item: &Index, file: &str) -> Result<&'a Value, SynError> {
+    match (item, toml) {
+        (Index::Help(item, help), toml) => get(toml, item, file).map_err(|e| with_help(e, help)),
+        (Index::Str(key, span), Value::Table(table)) => table.get(key).ok_or_else(|| {
+            SynError::new(*span, format!("Cannot find key \"{}\" in {}", key, file))
+        }),
+        (Index::Int(index, span), Value::Array(array)) => array
+            .get(*index)
+            .ok_or_else(|| SynError::new(*span, format!("Index {} is out of bounds", index))),
+        (Index::First(span), Value::Array(array)) => array
+            .first()
+            .ok_or_else(|| SynError::new(*span, "Cannot index empty array with first")),
+        (Index::Last(span), Value::Array(array)) => array
+            .last()
+            .ok_or_else(|| SynError::new(*span, "Cannot index empty array with last")),
+        (Index::Where(key, expected, span), Value::Array(array)) => {
+            find_in(array, key, expected).map_err(|e| SynError::new(*span, e))
+        }
+        (Index::First(span), other)
+        | (Index::Last(span), other)
+        | (Index::Where(_, _, span), other) => Err(SynError::new(
+            *span,
+            format!("Cannot index {} with {}", other.type_str(), item),
+        )),
+        (Index::Str(key, span), other) => Err(SynError::new(
+            *span,
+            format!("Cannot index {} with key \"{}\"", other.type_str(), key),
+        )),
+        (Index::Int(index, span), other) => Err(SynError::new(
+            *span,
+            format!("Cannot index {} with {}", other.type_str(), index),
+        )),
+    }
+}
+
+/// Appends `help` to the message of `error`.
+fn with_help(error: SynError, help: &str) -> SynError {
+    SynError::new(error.span(), format!("{}\nhelp: {}", error, help))
+}
+
+/// Runs [`lookup`], putting `message` in front of the message of its error.
+fn expect(index: TomlIndex, message: &LitStr, document: &Document) -> Result<Value, SynError> {
+    document
+        .lookup(index)
+        .map_err(|e| SynError::new(e.span(), format!("{}\nnote: {}", message.value(), e)))
+}
+
+/// Emits `then` if `predicate` holds for the compiled crate and `otherwise` if it does not.
+///
+/// Uses `#[cfg]` on match arms, so that only one of the values is type checked.
+fn conditional(
+    predicate: &TokenStream2,
+    then: TokenStream2,
+    otherwise: TokenStream2,
+) -> TokenStream2 {
+    quote! {
+        match () {
+            #[cfg(#predicate)]
+            () => #then,
+            #[cfg(not(#predicate))]
+            () => #otherwise,
+        }
+    }
+}
+
+/// Parse an array of bytes from `Cargo.toml` at compile time.
+///
+/// The selected value must be an array of integers in `0..=255`, which is emitted as a `[u8; N]` array literal.
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_byte_array;
+///
+/// // error: Expected array, found string
+/// let this_fails: [u8; 0] = include_toml_byte_array!("package"."name");
+/// ```
+#[proc_macro]
+pub fn include_toml_byte_array(input: TokenStream) -> TokenStream {
+    expand(input, to_byte_array)
+}
+
+/// Returns the elements held by `value`, or an error naming the actual type.
+fn array(value: Value) -> Result<Vec<Value>, SynError> {
+    match value {
+        Value::Array(a) => Ok(a),
+        other => Err(error(format!("Expected array, found {}", other.type_str()))),
+    }
+}
+
+/// Returns the integer held by `value`, or an error naming the actual type.
+fn integer(value: Value) -> Result<i64, SynError> {
+    match value {
+        Value::Integer(i) => Ok(i),
+        other => Err(error(format!(
+            "Expected integer, found {}",
+            other.type_str()
+        ))),
+    }
+}
+
+/// Converts an array of integers in `0..=255` to a `[u8; N]` literal.
+fn to_byte_array(value: Value) -> Result<TokenStream2, SynError> {
+    let mut bytes = Vec::new();
+    for (i, element) in array(value)?.into_iter().enumerate() {
+        let int = integer(element)?;
+        let byte = u8::try_from(int)
+            .map_err(|_| error(format!("Element {} is out of range for u8: {}", i, int)))?;
+        bytes.push(Literal::u8_suffixed(byte));
+    }
+    Ok(quote! ([#(#bytes),*]))
+}
+
+/// Parse an array of integers from `Cargo.toml` at compile time.
+///
+/// The selected value must be an array of integers, which is emitted as an `[i64; N]` array literal.
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_i64_array;
+///
+/// // error: Element 0: Expected integer, found string
+/// let this_fails: [i64; 5] = include_toml_i64_array!("package"."keywords");
+/// ```
+#[proc_macro]
+pub fn include_toml_i64_array(input: TokenStream) -> TokenStream {
+    expand(input, to_i64_array)
+}
+
+/// Converts an array of integers to an `[i64; N]` literal.
+fn to_i64_array(value: Value) -> Result<TokenStream2, SynError> {
+    let ints = array(value)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, element)| {
+            integer(element)
+                .map(Literal::i64_suffixed)
+                .map_err(|e| error(format!("Element {}: {}", i, e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(quote! ([#(#ints),*]))
+}
+
+/// Parse a key and its value from `Cargo.toml` at compile time.
+///
+/// Emits a tuple of the last key of the index and the value it refers to.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_entry;
+///
+/// assert_eq!(include_toml_entry!("dependencies"."syn"), ("syn", "1"));
+/// ```
+#[proc_macro]
+pub fn include_toml_entry(input: TokenStream) -> TokenStream {
+    expand_with(input, entry)
+}
+
+/// Looks up `index` and pairs the value with the last key of `index`.
+fn entry(index: TomlIndex, document: Document) -> Result<TokenStream2, SynError> {
+    let key = match index.0.last().and_then(Index::key) {
+        Some(key) => key.to_string(),
+        None => return Err(error("Last index item must be a key")),
+    };
+    let value = translate(document.lookup(index)?)?;
+    Ok(quote! ((#key, #value)))
+}
+
+/// Parse a part of `Cargo.toml` as TOML source at compile time.
+///
+/// Emits the TOML source of the selected value as [`&str`], including its comments and formatting.
+/// Tables are emitted as documents, with headers of nested tables relative to the selected table.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_source;
+///
+/// assert_eq!(include_toml_source!("lib"), "proc-macro = true\n");
+/// ```
+///
+/// Requires the `include_toml_source` feature, which is enabled by default.
+#[cfg(feature = "include_toml_source")]
+#[proc_macro]
+pub fn include_toml_source(input: TokenStream) -> TokenStream {
+    let index: TomlIndex = parse_macro_input!(input);
+    let path = manifest_dir().join("Cargo.toml");
+
+    parse_document(&path)
+        .and_then(|document| source(index, &document, &file_name(&path)))
+        .map(|source| Lit::new(Literal::string(&source)).to_token_stream())
+        .unwrap_or_else(SynError::into_compile_error)
+        .into()
+}
+
+/// Parses `path` keeping comments and formatting.
+#[cfg(feature = "include_toml_source")]
+fn parse_document(path: &Path) -> Result<DocumentMut, SynError> {
+    read(path)?
+        .parse()
+        .map_err(|e| error(format!("Cannot parse {}: {}", file_name(path), e)))
+}
+
+/// Looks up `index` in `document` read from `file` and renders the TOML source of the value.
+#[cfg(feature = "include_toml_source")]
+fn source(index: TomlIndex, document: &DocumentMut, file: &str) -> Result<String, SynError> {
+    let mut item = document.as_item();
+    for segment in index.0 {
+        item = get_item(item, &segment, file)?;
+    }
+    Ok(match item {
+        Item::Table(table) => DocumentMut::from(table.clone())
+            .to_string()
+            .trim_start()
+            .to_string(),
+        other => other.to_string().trim().to_string(),
+    })
+}
+
+/// Returns the item at `segment` of `item`, or an error pointing at `segment` that names `file`.
+#[cfg(feature = "include_toml_source")]
+fn get_item<'a>(item: &'a Item, segment: &Index, file: &str) -> Result<&'a Item, SynError> {
+    match segment {
+        Index::Int(index, span) => item.get(index).ok_or_else(|| {
+            SynError::new(*span, format!("Cannot find index {} in {}", index, file))
+        }),
+        Index::Str(key, span) => item.get(key).ok_or_else(|| {
+            SynError::new(*span, format!("Cannot find key \"{}\" in {}", key, file))
+        }),
+        Index::First(span) | Index::Last(span) => {
+            let len = match item {
+                Item::ArrayOfTables(tables) => tables.len(),
+                other => other.as_array().map_or(0, |array| array.len()),
+            };
+            let index = match segment {
+                Index::First(_) => 0,
+                _ => len.wrapping_sub(1),
+            };
+            item.get(index)
+                .ok_or_else(|| SynError::new(*span, format!("Cannot find {} in {}", segment, file)))
+        }
+        Index::Where(key, expected, span) => {
+            let mut matches = (0..).map_while(|i| item.get(i)).filter(|table| {
+                table
+                    .get(key)
+                    .and_then(Item::as_value)
+                    .is_some_and(|value| edit_equals(value, expected))
+            });
+            match (matches.next(), matches.next()) {
+                (Some(table), None) => Ok(table),
+                _ => Err(SynError::new(
+                    *span,
+                    format!("Cannot find {} in {}", segment, file),
+                )),
+            }
+        }
+        Index::Help(segment, help) => get_item(item, segment, file).map_err(|e| with_help(e, help)),
+    }
+}
+
+/// Returns whether the scalar `value` of a document equals `expected`.
+#[cfg(feature = "include_toml_source")]
+fn edit_equals(value: &EditValue, expected: &Value) -> bool {
+    match (value, expected) {
+        (EditValue::String(s), Value::String(expected)) => s.value() == expected,
+        (EditValue::Integer(i), Value::Integer(expected)) => i.value() == expected,
+        (EditValue::Float(f), Value::Float(expected)) => f.value() == expected,
+        (EditValue::Boolean(b), Value::Boolean(expected)) => b.value() == expected,
+        _ => false,
+    }
+}
+
+/// Largest denominator emitted by [`include_toml_ratio!`].
+const MAX_DENOMINATOR: i64 = 1_000_000;
+
+/// Parse a float from `Cargo.toml` as a fraction at compile time.
+///
+/// Emits an `(i64, i64)` tuple of numerator and denominator closest to the float,
+/// with a denominator of at most 1 000 000.
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_ratio;
+///
+/// // error: Expected float, found string
+/// let this_fails = include_toml_ratio!("package"."version");
+/// ```
+#[proc_macro]
+pub fn include_toml_ratio(input: TokenStream) -> TokenStream {
+    expand(input, to_ratio)
+}
+
+/// Returns the float held by `value`, or an error naming the actual type.
+fn float(value: Value) -> Result<f64, SynError> {
+    match value {
+        Value::Float(f) => Ok(f),
+        other => Err(error(format!("Expected float, found {}", other.type_str()))),
+    }
+}
+
+/// Converts a float to a `(numerator, denominator)` tuple.
+fn to_ratio(value: Value) -> Result<TokenStream2, SynError> {
+    let f = float(value)?;
+    if !f.is_finite() || f.abs() >= i64::MAX as f64 {
+        return Err(error(format!("Cannot represent {} as a fraction", f)));
+    }
+    let (numerator, denominator) = ratio(f, MAX_DENOMINATOR);
+    Ok(quote! ((#numerator, #denominator)))
+}
+
+/// Approximates `f` by the last convergent of its continued fraction with a denominator up to `max_denominator`.
+fn ratio(f: f64, max_denominator: i64) -> (i64, i64) {
+    let (mut h0, mut h1) = (0i64, 1i64);
+    let (mut k0, mut k1) = (1i64, 0i64);
+    let mut x = f;
+    loop {
+        let a = x.floor() as i64;
+        let next = a
+            .checked_mul(h1)
+            .and_then(|h| h.checked_add(h0))
+            .zip(a.checked_mul(k1).and_then(|k| k.checked_add(k0)));
+        match next {
+            Some((h2, k2)) if k2 <= max_denominator => {
+                h0 = h1;
+                h1 = h2;
+                k0 = k1;
+                k1 = k2;
+            }
+            _ => break,
+        }
+        let fraction = x - x.floor();
+        if fraction == 0.0 || h1 as f64 / k1 as f64 == f {
+            break;
+        }
+        x = 1.0 / fraction;
+    }
+    (h1, k1)
+}
+
+/// Parse a table from `Cargo.toml` as [`HashMap`](std::collections::HashMap) at compile time.
+///
+/// Emits an expression building a `HashMap<&str, T>` at runtime.
+/// All values of the table have to be scalars of the same type.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_hashmap;
+///
+/// let lib = include_toml_hashmap!("lib");
+/// assert!(lib["proc-macro"]);
+/// ```
+#[proc_macro]
+pub fn include_toml_hashmap(input: TokenStream) -> TokenStream {
+    expand(input, to_hashmap)
+}
+
+/// Returns the entries held by `value`, or an error naming the actual type.
+fn table(value: Value) -> Result<Table, SynError> {
+    match value {
+        Value::Table(t) => Ok(t),
+        other => Err(error(format!("Expected table, found {}", other.type_str()))),
+    }
+}
+
+/// Converts a table of scalars with the same type to an expression building a `HashMap`.
+fn to_hashmap(value: Value) -> Result<TokenStream2, SynError> {
+    let table = table(value)?;
+    let mut value_type = None;
+    let mut entries = Vec::new();
+    for (key, value) in table {
+        if let Value::Array(_) | Value::Table(_) = value {
+            return Err(error(format!(
+                "Expected scalar for key \"{}\", found {}",
+                key,
+                value.type_str()
+            )));
+        }
+        match value_type {
+            Some(t) if t != value.type_str() => {
+                return Err(error(format!(
+                    "Expected {} for key \"{}\", found {}",
+                    t,
+                    key,
+                    value.type_str()
+                )))
+            }
+            _ => value_type = Some(value.type_str()),
+        }
+        let value = translate(value)?;
+        entries.push(quote! ((#key, #value)));
+    }
+    Ok(quote! (::std::collections::HashMap::from([#(#entries),*])))
+}
+
+/// Struct that parses input of [`include_toml_range`].
+///
+/// Input should consist of a [`TomlIndex`], a comma and a range of integer literals, e.g. `1..=65535`.
+/// Both bounds are optional.
+struct RangeInput {
+    index: TomlIndex,
+    start: Option<i64>,
+    end: Option<i64>,
+    inclusive: bool,
+}
+
+impl Parse for RangeInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        input.parse::<Comma>()?;
+        let start = if input.peek(Dot2) || input.peek(DotDotEq) {
+            None
+        } else {
+            Some(parse_i64(input)?)
+        };
+        let inclusive = if input.peek(DotDotEq) {
+            input.parse::<DotDotEq>()?;
+            true
+        } else {
+            input.parse::<Dot2>()?;
+            false
+        };
+        let end = if input.is_empty() {
+            None
+        } else {
+            Some(parse_i64(input)?)
+        };
+        Ok(Self {
+            index,
+            start,
+            end,
+            inclusive,
+        })
+    }
+}
+
+/// Parses an integer literal with optional leading minus.
+fn parse_i64(input: &ParseBuffer) -> Result<i64, SynError> {
+    let negative = input.parse::<Option<Sub>>()?.is_some();
+    let int: LitInt = input.parse()?;
+    let value: i64 = int.base10_parse()?;
+    Ok(if negative { -value } else { value })
+}
+
+/// Parse an integer from `Cargo.toml` within a range at compile time.
+///
+/// Emits the integer like [`include_toml!`], or a `compile_error!` if it is outside of the range.
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_range;
+///
+/// // error: Expected integer, found string
+/// let this_fails = include_toml_range!("package"."version", 1..=65535);
+/// ```
+#[proc_macro]
+pub fn include_toml_range(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: RangeInput, document| {
+        let value = integer(document.lookup(input.index)?)?;
+        check_range(value, input.start, input.end, input.inclusive)?;
+        translate(Value::Integer(value))
+    })
+}
+
+/// Fails if `value` is outside of the range from `start` to `end`.
+fn check_range(
+    value: i64,
+    start: Option<i64>,
+    end: Option<i64>,
+    inclusive: bool,
+) -> Result<(), SynError> {
+    let above_start = match start {
+        Some(start) => value >= start,
+        None => true,
+    };
+    let below_end = match end {
+        Some(end) if inclusive => value <= end,
+        Some(end) => value < end,
+        None => true,
+    };
+    if above_start && below_end {
+        return Ok(());
+    }
+    let bound = |b: Option<i64>| b.map(|b| b.to_string()).unwrap_or_default();
+    Err(error(format!(
+        "Value {} is out of range {}{}{}",
+        value,
+        bound(start),
+        if inclusive { "..=" } else { ".." },
+        bound(end)
+    )))
+}
+
+/// Parse an optional value from `Cargo.toml` at compile time.
+///
+/// Emits [`None`] if the key is missing or the value is an empty string or an empty table, and [`Some`] of the value otherwise.
+/// Other lookup errors, like indexing into a scalar, cause a `compile_error!`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_opt;
+///
+/// let version: Option<&str> = include_toml_opt!("package"."version");
+/// let homepage: Option<&str> = include_toml_opt!("package"."homepage");
+/// assert_eq!(version, Some("0.3.1"));
+/// assert_eq!(homepage, None);
+/// ```
+#[proc_macro]
+pub fn include_toml_opt(input: TokenStream) -> TokenStream {
+    expand_with(input, |index: TomlIndex, document| {
+        to_option(document.lookup_opt(index)?)
+    })
+}
+
+/// Converts a value to `Some(value)`, or to `None` if it is missing or empty.
+fn to_option(value: Option<Value>) -> Result<TokenStream2, SynError> {
+    match value {
+        None => Ok(quote!(::core::option::Option::None)),
+        Some(Value::String(s)) if s.is_empty() => Ok(quote!(::core::option::Option::None)),
+        Some(Value::Table(t)) if t.is_empty() => Ok(quote!(::core::option::Option::None)),
+        Some(value) => {
+            let value = translate(value)?;
+            Ok(quote!(::core::option::Option::Some(#value)))
+        }
+    }
+}
+
+/// Parse a hash of a value from `Cargo.toml` at compile time.
+///
+/// Emits the 64 bit [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hash of the value's string form as [`u64`].
+/// The hash only depends on the value, so it is the same on every platform.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_hash;
+///
+/// assert_eq!(include_toml_hash!("lib"."proc-macro"), 0x5b5c98ef514dbfa5);
+/// ```
+#[proc_macro]
+pub fn include_toml_hash(input: TokenStream) -> TokenStream {
+    expand(input, |value| {
+        let hash = fnv1a(scalar_string(value)?.as_bytes());
+        Ok(Lit::new(Literal::u64_suffixed(hash)).to_token_stream())
+    })
+}
+
+/// Returns the string form of a scalar value, or an error for arrays and tables.
+///
+/// Strings are returned without quotes.
+fn scalar_string(value: Value) -> Result<String, SynError> {
+    match value {
+        Value::String(s) => Ok(s),
+        Value::Integer(i) => Ok(i.to_string()),
+        Value::Float(f) => Ok(f.to_string()),
+        Value::Boolean(b) => Ok(b.to_string()),
+        Value::Datetime(d) => Ok(d.to_string()),
+        other => Err(error(format!(
+            "Expected scalar, found {}",
+            other.type_str()
+        ))),
+    }
+}
+
+/// Computes the 64 bit FNV-1a hash of `bytes`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Parse a boolean from `Cargo.toml` with lenient coercion at compile time.
+///
+/// Accepts booleans as well as the following values, and emits a [`bool`] literal:
+///
+/// - `true`: `"true"`, `"yes"`, `"on"`, `"1"` and `1`
+/// - `false`: `"false"`, `"no"`, `"off"`, `"0"` and `0`
+///
+/// Strings are compared ignoring ASCII case. Any other value causes a `compile_error!`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_truthy;
+///
+/// assert!(include_toml_truthy!("lib"."proc-macro"));
+/// ```
+#[proc_macro]
+pub fn include_toml_truthy(input: TokenStream) -> TokenStream {
+    expand(input, |value| {
+        let b = truthy(value)?;
+        Ok(Lit::Bool(LitBool::new(b, Span2::call_site())).to_token_stream())
+    })
+}
+
+/// Coerces common representations of booleans.
+fn truthy(value: Value) -> Result<bool, SynError> {
+    match value {
+        Value::Boolean(b) => Ok(b),
+        Value::Integer(1) => Ok(true),
+        Value::Integer(0) => Ok(false),
+        Value::String(ref s) => match s.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Ok(true),
+            "false" | "no" | "off" | "0" => Ok(false),
+            _ => Err(error(format!("Cannot interpret {} as boolean", value))),
+        },
+        other => Err(error(format!("Cannot interpret {} as boolean", other))),
+    }
+}
+
+/// Struct that parses input of [`include_toml_find`].
+///
+/// Input should consist of a [`TomlIndex`] of an array, the key and literal value to search for,
+/// and a [`TomlIndex`] into the found table, separated by commas.
+struct FindInput {
+    array: TomlIndex,
+    key: String,
+    expected: Value,
+    index: TomlIndex,
+}
+
+impl Parse for FindInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let array = input.parse()?;
+        input.parse::<Comma>()?;
+        let key = input.parse::<LitStr>()?.value();
+        input.parse::<Comma>()?;
+        let expected = lit_value(&input.parse()?)?;
+        input.parse::<Comma>()?;
+        let index = input.parse()?;
+        Ok(Self {
+            array,
+            key,
+            expected,
+            index,
+        })
+    }
+}
+
+/// Converts a literal to the TOML value it represents.
+fn lit_value(lit: &Lit) -> Result<Value, SynError> {
+    match lit {
+        Lit::Str(s) => Ok(Value::String(s.value())),
+        Lit::Int(i) => Ok(Value::Integer(i.base10_parse()?)),
+        Lit::Float(f) => Ok(Value::Float(f.base10_parse()?)),
+        Lit::Bool(b) => Ok(Value::Boolean(b.value)),
+        _ => Err(SynError::new(lit.span(), "Unsupported literal")),
+    }
+}
+
+/// Parse a value from a table in an array of `Cargo.toml` at compile time.
+///
+/// Searches the array for the table whose `key` equals the given value and looks up the index in that table.
+/// Fails if no table or more than one table matches.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_find;
+///
+/// // path of [[bin]] with name = "mytool"
+/// let path = include_toml_find!(first ["tests/fixtures/tables.toml"], "bin", "name", "mytool", "path");
+/// assert_eq!(path, "src/mytool.rs");
+/// ```
+#[proc_macro]
+pub fn include_toml_find(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: FindInput, document| {
+        let table = find(document.lookup(input.array)?, &input.key, &input.expected)?;
+        translate(lookup_in(input.index, &table, &document.file)?)
+    })
+}
+
+/// Returns the only element of the array `tables` whose `key` equals `expected`.
+fn find(tables: Value, key: &str, expected: &Value) -> Result<Value, SynError> {
+    find_in(&array(tables)?, key, expected).cloned()
+}
+
+/// Returns the only element of `tables` whose `key` equals `expected`.
+fn find_in<'a>(tables: &'a [Value], key: &str, expected: &Value) -> Result<&'a Value, SynError> {
+    let mut matches = tables
+        .iter()
+        .filter(|element| element.get(key) == Some(expected));
+    match (matches.next(), matches.next()) {
+        (Some(table), None) => Ok(table),
+        (None, _) => Err(error(format!(
+            "Cannot find table with \"{}\" = {} in array",
+            key, expected
+        ))),
+        (Some(_), Some(_)) => Err(error(format!(
+            "Found {} tables with \"{}\" = {} in array, expected one",
+            2 + matches.count(),
+            key,
+            expected
+        ))),
+    }
+}
+
+/// Struct that parses input of [`include_toml_into`].
+///
+/// Input should consist of a type, a comma and a [`TomlIndex`].
+struct IntoInput {
+    ty: Type,
+    index: TomlIndex,
+}
+
+impl Parse for IntoInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let ty = input.parse()?;
+        input.parse::<Comma>()?;
+        let index = input.parse()?;
+        Ok(Self { ty, index })
+    }
+}
+
+/// Parse a value from `Cargo.toml` at compile time and convert it to a custom type.
+///
+/// Emits `<Type as From<T>>::from(value)`, where `value` is emitted like [`include_toml!`].
+/// Proc-macro crates cannot export traits, so conversions are implemented using [`From`]
+/// for the Rust type of the value, e.g. a tuple for tables.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_into;
+///
+/// struct Version(String);
+///
+/// impl From<&str> for Version {
+///     fn from(version: &str) -> Self {
+///         Version(version.to_string())
+///     }
+/// }
+///
+/// let version = include_toml_into!(Version, "package"."version");
+/// assert_eq!(version.0, "0.3.1");
+/// ```
+#[proc_macro]
+pub fn include_toml_into(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: IntoInput, document| {
+        let value = translate(document.lookup(input.index)?)?;
+        Ok(into(&input.ty, value))
+    })
+}
+
+/// Emits the conversion of `value` to `ty` using [`From`].
+fn into(ty: &Type, value: TokenStream2) -> TokenStream2 {
+    quote!(<#ty as ::core::convert::From<_>>::from(#value))
+}
+
+/// Struct that parses input of [`include_toml_merged`].
+///
+/// Input should consist of a list of paths in brackets, a comma and a [`TomlIndex`].
+struct MergedInput {
+    paths: Vec<String>,
+    index: TomlIndex,
+}
+
+impl Parse for MergedInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let content;
+        bracketed!(content in input);
+        let paths = Punctuated::<LitStr, Comma>::parse_terminated(&content)?;
+        input.parse::<Comma>()?;
+        let index = input.parse()?;
+        Ok(Self {
+            paths: paths.iter().map(LitStr::value).collect(),
+            index,
+        })
+    }
+}
+
+/// Parse a value from several merged TOML files at compile time.
+///
+/// Paths are relative to the directory containing `Cargo.toml`. The files are merged in order:
+///
+/// - Tables are merged recursively, keeping keys of all files.
+/// - Any other value of a later file replaces the value of earlier files, including arrays.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_merged;
+///
+/// let port = include_toml_merged!(["tests/fixtures/config.toml", "tests/fixtures/override.toml"], "server"."port");
+/// assert_eq!(port, 9090);
+/// ```
+#[proc_macro]
+pub fn include_toml_merged(input: TokenStream) -> TokenStream {
+    let MergedInput { paths, index } = parse_macro_input!(input);
+    let dir = manifest_dir();
+
+    paths
+        .iter()
+        .map(|path| parse(&dir.join(path)))
+        .try_fold(Value::Table(Table::new()), |merged, toml| {
+            Ok(merge(merged, toml?))
+        })
+        .and_then(|toml| lookup_in(index, &toml, &format!("any of {:?}", paths)))
+        .and_then(translate)
+        .unwrap_or_else(SynError::into_compile_error)
+        .into()
+}
+
+/// Merges `overlay` into `base`, merging tables recursively and replacing any other value.
+fn merge(base: Value, overlay: Value) -> Value {
+    match (base, overlay) {
+        (Value::Table(mut base), Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                let merged = match base.remove(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => value,
+                };
+                base.insert(key, merged);
+            }
+            Value::Table(base)
+        }
+        (_, overlay) => overlay,
+    }
+}
+
+/// Parse whether a table or array of `Cargo.toml` is empty at compile time.
+///
+/// Emits `true` if the key is missing or the value is an empty table or array, and `false` otherwise.
+/// Scalars and other lookup errors, like indexing into a scalar, cause a `compile_error!`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_is_empty;
+///
+/// assert!(!include_toml_is_empty!("dependencies"));
+/// assert!(include_toml_is_empty!("build-dependencies"));
+/// ```
+#[proc_macro]
+pub fn include_toml_is_empty(input: TokenStream) -> TokenStream {
+    expand_with(input, |index: TomlIndex, document| {
+        let empty = is_empty(document.lookup_opt(index)?)?;
+        Ok(Lit::Bool(LitBool::new(empty, Span2::call_site())).to_token_stream())
+    })
+}
+
+/// Returns whether `value` is missing or an empty table or array.
+fn is_empty(value: Option<Value>) -> Result<bool, SynError> {
+    match value {
+        None => Ok(true),
+        Some(Value::Table(t)) => Ok(t.is_empty()),
+        Some(Value::Array(a)) => Ok(a.is_empty()),
+        Some(other) => Err(error(format!(
+            "Expected table or array, found {}",
+            other.type_str()
+        ))),
+    }
+}
+
+/// Parse an array of strings from `Cargo.toml` as sorted set at compile time.
+///
+/// Emits a `&[&str]` slice with the strings sorted and duplicates removed, e.g. for [`slice::binary_search`].
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_set;
+///
+/// let keywords: &[&str] = include_toml_set!("package"."keywords");
+/// assert_eq!(keywords, ["Cargo-toml", "compile-time", "macro", "parse", "version"]);
+/// assert!(keywords.binary_search(&"macro").is_ok());
+/// ```
+#[proc_macro]
+pub fn include_toml_set(input: TokenStream) -> TokenStream {
+    expand(input, to_set)
+}
+
+/// Converts an array of strings to a sorted `&[&str]` without duplicates.
+fn to_set(value: Value) -> Result<TokenStream2, SynError> {
+    let mut strings = strings(value)?;
+    strings.sort();
+    strings.dedup();
+    Ok(quote!(&[#(#strings),*]))
+}
+
+/// Parse an array of strings from `Cargo.toml` at compile time.
+///
+/// Emits a `&[&str]` slice with the strings in order, e.g. for the globs of `package.include` and `package.exclude`.
+/// An empty array is emitted as an empty slice, while a missing key is an error.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_strs;
+///
+/// let categories: &[&str] = include_toml_strs!("package"."categories");
+/// assert_eq!(categories[0], "command-line-interface");
+/// ```
+#[proc_macro]
+pub fn include_toml_strs(input: TokenStream) -> TokenStream {
+    expand(input, to_strs)
+}
+
+/// Converts an array of strings to a `&[&str]`.
+fn to_strs(value: Value) -> Result<TokenStream2, SynError> {
+    let strings = strings(value)?;
+    Ok(quote!(&[#(#strings),*]))
+}
+
+/// Returns the strings held by an array, or an error naming the first element of another type.
+fn strings(value: Value) -> Result<Vec<String>, SynError> {
+    array(value)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, element)| string(element).map_err(|e| error(format!("Element {}: {}", i, e))))
+        .collect()
+}
+
+/// Struct that parses input of [`include_toml_as`].
+///
+/// Input should consist of a [`TomlIndex`], a comma and the name of a primitive numeric type.
+struct AsInput {
+    index: TomlIndex,
+    ty: Ident,
+}
+
+impl Parse for AsInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        input.parse::<Comma>()?;
+        let ty = input.parse()?;
+        Ok(Self { index, ty })
+    }
+}
+
+/// Parse a number from `Cargo.toml` as a given numeric type at compile time.
+///
+/// Emits a literal with the type as suffix, e.g. `8080u16`.
+/// Integers can be emitted as any primitive integer or float type, and have to fit into integer types.
+/// Floats can only be emitted as `f32` or `f64`, and have to be finite in that type.
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_as;
+///
+/// // error: Expected integer or float, found string
+/// let this_fails = include_toml_as!("package"."version", u16);
+/// ```
+#[proc_macro]
+pub fn include_toml_as(input: TokenStream) -> TokenStream {
+    expand_with(input, |AsInput { index, ty }, document| {
+        to_type(document.lookup(index)?, &ty)
+    })
+}
+
+/// Converts a number to a literal suffixed with the primitive numeric type `ty`.
+fn to_type(value: Value, ty: &Ident) -> Result<TokenStream2, SynError> {
+    let ty_name = ty.to_string();
+    let out_of_range = |value: &dyn Debug| {
+        SynError::new(
+            ty.span(),
+            format!("Value {:?} is out of range for {}", value, ty),
+        )
+    };
+    let int = match value {
+        Value::Integer(i) => i,
+        Value::Float(f) => {
+            return match ty_name.as_str() {
+                "f32" if !f.is_finite() || !(f as f32).is_finite() => Err(out_of_range(&f)),
+                "f64" if !f.is_finite() => Err(out_of_range(&f)),
+                "f32" => Ok(Literal::f32_suffixed(f as f32).to_token_stream()),
+                "f64" => Ok(Literal::f64_suffixed(f).to_token_stream()),
+                _ => Err(SynError::new(
+                    ty.span(),
+                    format!("Cannot emit float {} as {}", f, ty),
+                )),
+            }
+        }
+        other => {
+            return Err(error(format!(
+                "Expected integer or float, found {}",
+                other.type_str()
+            )))
+        }
+    };
+    let out_of_range = || out_of_range(&int);
+    let literal = match ty_name.as_str() {
+        "i8" => Literal::i8_suffixed(i8::try_from(int).map_err(|_| out_of_range())?),
+        "i16" => Literal::i16_suffixed(i16::try_from(int).map_err(|_| out_of_range())?),
+        "i32" => Literal::i32_suffixed(i32::try_from(int).map_err(|_| out_of_range())?),
+        "i64" => Literal::i64_suffixed(int),
+        "i128" => Literal::i128_suffixed(i128::from(int)),
+        "isize" => Literal::isize_suffixed(isize::try_from(int).map_err(|_| out_of_range())?),
+        "u8" => Literal::u8_suffixed(u8::try_from(int).map_err(|_| out_of_range())?),
+        "u16" => Literal::u16_suffixed(u16::try_from(int).map_err(|_| out_of_range())?),
+        "u32" => Literal::u32_suffixed(u32::try_from(int).map_err(|_| out_of_range())?),
+        "u64" => Literal::u64_suffixed(u64::try_from(int).map_err(|_| out_of_range())?),
+        "u128" => Literal::u128_suffixed(u128::try_from(int).map_err(|_| out_of_range())?),
+        "usize" => Literal::usize_suffixed(usize::try_from(int).map_err(|_| out_of_range())?),
+        "f32" => Literal::f32_suffixed(int as f32),
+        "f64" => Literal::f64_suffixed(int as f64),
+        _ => {
+            return Err(SynError::new(
+                ty.span(),
+                format!("Unsupported type {}, expected primitive numeric type", ty),
+            ))
+        }
+    };
+    Ok(literal.to_token_stream())
+}
+
+/// Struct that parses input of [`include_toml_structs`].
+///
+/// Input should consist of a struct path, a [`TomlIndex`] of an array and fields in braces,
+/// which map field names to a [`TomlIndex`] into each table, e.g. `{ name: "name", path: "path" }`.
+struct StructsInput {
+    ty: SynPath,
+    index: TomlIndex,
+    fields: Vec<(Ident, TomlIndex)>,
+}
+
+impl Parse for StructsInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let ty = input.parse()?;
+        input.parse::<Comma>()?;
+        let index = input.parse()?;
+        input.parse::<Comma>()?;
+        let content;
+        braced!(content in input);
+        let mut fields = Vec::new();
+        while !content.is_empty() {
+            let field = content.parse()?;
+            content.parse::<Colon>()?;
+            fields.push((field, content.parse()?));
+            if content.parse::<Option<Comma>>()?.is_none() {
+                break;
+            }
+        }
+        if !content.is_empty() {
+            return Err(content.error("Expected `,`"));
+        }
+        Ok(Self { ty, index, fields })
+    }
+}
+
+/// Parse an array of tables from `Cargo.toml` as a slice of structs at compile time.
+///
+/// Emits a `&[Type]` slice with one struct expression per table,
+/// whose fields are looked up in the table as given by the field mapping.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_structs;
+///
+/// struct Entry {
+///     name: &'static str,
+///     path: &'static str,
+/// }
+///
+/// // [[bin]]
+/// // name = "server"
+/// // path = "src/server.rs"
+/// const BINARIES: &[Entry] = include_toml_structs!(
+///     first ["tests/fixtures/tables.toml"],
+///     Entry,
+///     "bin",
+///     { name: "name", path: "path" }
+/// );
+/// assert_eq!(BINARIES[0].path, "src/server.rs");
+/// ```
+#[proc_macro]
+pub fn include_toml_structs(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: StructsInput, document| {
+        let value = document.lookup(input.index)?;
+        to_structs(&input.ty, input.fields, value, &document.file)
+    })
+}
+
+/// Converts an array of tables read from `file` to a slice of `ty` struct expressions with the given fields.
+fn to_structs(
+    ty: &SynPath,
+    fields: Vec<(Ident, TomlIndex)>,
+    value: Value,
+    file: &str,
+) -> Result<TokenStream2, SynError> {
+    let tables = array(value)?;
+    let mut structs = Vec::new();
+    for (i, table) in tables.into_iter().enumerate() {
+        let mut values = Vec::new();
+        for (field, index) in &fields {
+            let value = lookup_in(index.clone(), &table, file)
+                .and_then(translate)
+                .map_err(|e| error(format!("Element {}: {}", i, e)))?;
+            values.push(quote!(#field: #value));
+        }
+        structs.push(quote!(#ty { #(#values),* }));
+    }
+    Ok(quote!(&[#(#structs),*]))
+}
+
+/// Parse a value from `Cargo.toml` at compile time, deferring errors to runtime.
+///
+/// Emits `Ok(value)` of type `Result<T, &'static str>` if the value can be looked up and translated,
+/// and `Err(message)` with the error message otherwise. This never causes a `compile_error!` for missing values.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_result;
+///
+/// let version: Result<&str, &str> = include_toml_result!("package"."version");
+/// let homepage: Result<&str, &str> = include_toml_result!("package"."homepage");
+/// assert_eq!(version, Ok("0.3.1"));
+/// assert_eq!(homepage, Err("Cannot find key \"homepage\" in Cargo.toml"));
+/// ```
+#[proc_macro]
+pub fn include_toml_result(input: TokenStream) -> TokenStream {
+    expand_with(input, |index: TomlIndex, document| {
+        Ok(to_result(document.lookup(index).and_then(translate)))
+    })
+}
+
+/// Converts the outcome of looking up and translating a value to a `Result` expression.
+fn to_result(result: Result<TokenStream2, SynError>) -> TokenStream2 {
+    match result {
+        Ok(value) => quote!(::core::result::Result::<_, &'static str>::Ok(#value)),
+        Err(e) => {
+            let message = e.to_string();
+            quote!(::core::result::Result::<_, &'static str>::Err(#message))
+        }
+    }
+}
+
+/// Parse a value from `Cargo.toml` at compile time and construct it once at runtime.
+///
+/// Emits an accessor function `fn() -> &'static T`, which constructs the value emitted by [`include_toml!`]
+/// in a [`OnceLock`](std::sync::OnceLock) on first call and returns a reference to it afterwards.
+/// This avoids inlining large tuples at every use.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_once;
+///
+/// let keywords = include_toml_once!("package"."keywords");
+/// assert_eq!(keywords().0, "macro");
+/// assert!(std::ptr::eq(keywords(), keywords()));
+/// ```
+#[proc_macro]
+pub fn include_toml_once(input: TokenStream) -> TokenStream {
+    expand(input, |value| {
+        let ty = rust_type(&value);
+        let value = translate(value)?;
+        Ok(quote! {{
+            fn accessor() -> &'static #ty {
+                static VALUE: ::std::sync::OnceLock<#ty> = ::std::sync::OnceLock::new();
+                VALUE.get_or_init(|| #value)
+            }
+            accessor
+        }})
+    })
+}
+
+/// Returns the Rust type of `value` as emitted by [`translate`].
+fn rust_type(value: &Value) -> TokenStream2 {
+    match value {
+        Value::String(_) | Value::Datetime(_) => quote!(&'static str),
+        Value::Integer(_) => quote!(i64),
+        Value::Float(_) => quote!(f64),
+        Value::Boolean(_) => quote!(bool),
+        Value::Array(a) => {
+            let types = a.iter().map(rust_type);
+            quote!((#(#types,)*))
+        }
+        Value::Table(t) => {
+            let types = t.values().map(rust_type);
+            quote!((#((&'static str, #types),)*))
+        }
+    }
+}
+
+/// Parse a local date from `Cargo.toml` at compile time.
+///
+/// The selected value must be a TOML local date like `1979-05-27`,
+/// which is emitted as a `(year, month, day)` tuple of type `(u16, u8, u8)`.
+/// Other datetime kinds are rejected.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_date;
+///
+/// // [package.metadata]
+/// // released = 1979-05-27
+/// const RELEASED: (u16, u8, u8) = include_toml_date!(
+///     first ["tests/fixtures/metadata.toml"],
+///     "package"."metadata"."released"
+/// );
+/// assert_eq!(RELEASED, (1979, 5, 27));
+/// ```
+#[proc_macro]
+pub fn include_toml_date(input: TokenStream) -> TokenStream {
+    expand(input, to_date)
+}
+
+/// Parse a local time from `Cargo.toml` at compile time.
+///
+/// The selected value must be a TOML local time like `07:32:00.5`,
+/// which is emitted as a `(hour, minute, second, nanosecond)` tuple of type `(u8, u8, u8, u32)`.
+/// Other datetime kinds are rejected.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_time;
+///
+/// // [package.metadata]
+/// // backup = 03:00:00
+/// const BACKUP: (u8, u8, u8, u32) = include_toml_time!(
+///     first ["tests/fixtures/metadata.toml"],
+///     "package"."metadata"."backup"
+/// );
+/// assert_eq!(BACKUP, (3, 0, 0, 0));
+/// ```
+#[proc_macro]
+pub fn include_toml_time(input: TokenStream) -> TokenStream {
+    expand(input, to_time)
+}
+
+/// Returns the datetime held by `value`, or an error naming the actual type.
+fn datetime(value: Value) -> Result<Datetime, SynError> {
+    match value {
+        Value::Datetime(d) => Ok(d),
+        other => Err(error(format!(
+            "Expected datetime, found {}",
+            other.type_str()
+        ))),
+    }
+}
+
+/// Returns the name of the TOML datetime kind of `datetime`.
+fn datetime_kind(datetime: &Datetime) -> &'static str {
+    match (&datetime.date, &datetime.time, &datetime.offset) {
+        (Some(_), Some(_), Some(_)) => "offset datetime",
+        (Some(_), Some(_), None) => "local datetime",
+        (Some(_), None, _) => "local date",
+        (None, _, _) => "local time",
+    }
+}
+
+/// Converts a local date to a `(year, month, day)` tuple.
+fn to_date(value: Value) -> Result<TokenStream2, SynError> {
+    let datetime = datetime(value)?;
+    match (datetime.date, datetime.time) {
+        (Some(date), None) => Ok(date_tuple(&date)),
+        _ => Err(error(format!(
+            "Expected local date, found {}",
+            datetime_kind(&datetime)
+        ))),
+    }
+}
+
+/// Converts a local time to a `(hour, minute, second, nanosecond)` tuple.
+fn to_time(value: Value) -> Result<TokenStream2, SynError> {
+    let datetime = datetime(value)?;
+    match (datetime.date, datetime.time) {
+        (None, Some(time)) => Ok(time_tuple(&time)),
+        _ => Err(error(format!(
+            "Expected local time, found {}",
+            datetime_kind(&datetime)
+        ))),
+    }
+}
+
+/// Converts a date to a `(year, month, day)` tuple.
+fn date_tuple(date: &Date) -> TokenStream2 {
+    let year = Literal::u16_suffixed(date.year);
+    let month = Literal::u8_suffixed(date.month);
+    let day = Literal::u8_suffixed(date.day);
+    quote!((#year, #month, #day))
+}
+
+/// Converts a time to a `(hour, minute, second, nanosecond)` tuple.
+fn time_tuple(time: &Time) -> TokenStream2 {
+    let hour = Literal::u8_suffixed(time.hour);
+    let minute = Literal::u8_suffixed(time.minute);
+    let second = Literal::u8_suffixed(time.second);
+    let nanosecond = Literal::u32_suffixed(time.nanosecond);
+    quote!((#hour, #minute, #second, #nanosecond))
+}
+
+/// Converts a datetime to a tuple of its components.
+///
+/// Local dates and times are emitted like [`date_tuple`] and [`time_tuple`], local datetimes as `(date, time)`
+/// and offset datetimes as `(date, time, offset)` with the offset in minutes as [`i16`].
+fn datetime_tuple(datetime: &Datetime) -> TokenStream2 {
+    match (&datetime.date, &datetime.time, &datetime.offset) {
+        (Some(date), None, _) => date_tuple(date),
+        (None, Some(time), _) => time_tuple(time),
+        (Some(date), Some(time), None) => {
+            let (date, time) = (date_tuple(date), time_tuple(time));
+            quote!((#date, #time))
+        }
+        (Some(date), Some(time), Some(offset)) => {
+            let (date, time) = (date_tuple(date), time_tuple(time));
+            let offset = Literal::i16_suffixed(offset_minutes(offset));
+            quote!((#date, #time, #offset))
+        }
+        (None, None, _) => quote!(()),
+    }
+}
+
+/// Returns the offset from UTC in minutes.
+fn offset_minutes(offset: &Offset) -> i16 {
+    match offset {
+        Offset::Z => 0,
+        Offset::Custom { minutes } => *minutes,
+    }
+}
+
+/// Returns the seconds since the Unix epoch of an offset datetime.
+fn timestamp(datetime: &Datetime) -> Result<i64, SynError> {
+    match (&datetime.date, &datetime.time, &datetime.offset) {
+        (Some(date), Some(time), Some(offset)) => {
+            // Days since the epoch of the proleptic Gregorian calendar, see http://howardhinnant.github.io/date_algorithms.html
+            let (month, day) = (i64::from(date.month), i64::from(date.day));
+            let year = i64::from(date.year) - i64::from(month <= 2);
+            let era = year.div_euclid(400);
+            let year_of_era = year - era * 400;
+            let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+            let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+            let days = era * 146_097 + day_of_era - 719_468;
+            let seconds =
+                i64::from(time.hour) * 3600 + i64::from(time.minute) * 60 + i64::from(time.second);
+            Ok(days * 86_400 + seconds - i64::from(offset_minutes(offset)) * 60)
+        }
+        _ => Err(error(format!(
+            "Expected offset datetime for timestamp, found {}",
+            datetime_kind(datetime)
+        ))),
+    }
+}
+
+/// Struct that parses input of [`include_toml_call`].
+///
+/// Input should consist of a path to a function, a comma and a [`TomlIndex`].
+struct CallInput {
+    func: SynPath,
+    index: TomlIndex,
+}
+
+impl Parse for CallInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let func = input.parse()?;
+        input.parse::<Comma>()?;
+        let index = input.parse()?;
+        Ok(Self { func, index })
+    }
+}
+
+/// Parse an array from `Cargo.toml` at compile time and pass its elements to a function.
+///
+/// Emits `func(a, b, c)`, where each element is emitted like [`include_toml!`]
+/// and passed as a positional argument.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_call;
+///
+/// fn first(first: &str, _: &str, _: &str, _: &str, _: &str) -> String {
+///     first.to_string()
+/// }
+///
+/// assert_eq!(include_toml_call!(first, "package"."keywords"), "macro");
+/// ```
+#[proc_macro]
+pub fn include_toml_call(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: CallInput, document| {
+        call(&input.func, document.lookup(input.index)?)
+    })
+}
+
+/// Emits a call of `func` with the elements of the array `value` as arguments.
+fn call(func: &SynPath, value: Value) -> Result<TokenStream2, SynError> {
+    let args = array(value)?
+        .into_iter()
+        .map(translate)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(quote!(#func(#(#args),*)))
+}
+
+/// Parse an array with unique elements from `Cargo.toml` at compile time.
+///
+/// Emits the array like [`include_toml!`], or a compile error if two elements are equal.
+/// Elements of different types are never equal, e.g. `1` and `1.0`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_assert_unique;
+///
+/// let keywords = include_toml_assert_unique!("package"."keywords");
+/// assert_eq!(keywords.0, "macro");
+/// ```
+#[proc_macro]
+pub fn include_toml_assert_unique(input: TokenStream) -> TokenStream {
+    expand(input, |value| translate(unique(value)?))
+}
+
+/// Returns `value` if it is an array without equal elements.
+fn unique(value: Value) -> Result<Value, SynError> {
+    let elements = array(value)?;
+    for (j, element) in elements.iter().enumerate() {
+        if let Some(i) = elements[..j].iter().position(|other| other == element) {
+            return Err(error(format!(
+                "Element {} is a duplicate of element {}",
+                j, i
+            )));
+        }
+    }
+    Ok(Value::Array(elements))
+}
+
+/// Struct that parses input of [`include_toml_diff`].
+///
+/// Input should consist of two paths and a [`TomlIndex`], separated by commas.
+struct DiffInput {
+    a: LitStr,
+    b: LitStr,
+    index: TomlIndex,
+}
+
+impl Parse for DiffInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let a = input.parse()?;
+        input.parse::<Comma>()?;
+        let b = input.parse()?;
+        input.parse::<Comma>()?;
+        let index = input.parse()?;
+        Ok(Self { a, b, index })
+    }
+}
+
+/// Parse the difference of two arrays of strings from two TOML files at compile time.
+///
+/// Paths are relative to the directory containing `Cargo.toml`.
+/// Emits a `&[&str]` slice with the strings of the first file that are missing in the second file,
+/// in the order of the first file.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_diff;
+///
+/// let keywords: &[&str] = include_toml_diff!(
+///     "tests/fixtures/workspace/crates/core/Cargo.toml",
+///     "tests/fixtures/workspace/crates/macros/Cargo.toml",
+///     "package"."keywords"
+/// );
+/// assert_eq!(keywords, ["toml", "cargo"]);
+/// ```
+#[proc_macro]
+pub fn include_toml_diff(input: TokenStream) -> TokenStream {
+    let DiffInput { a, b, index } = parse_macro_input!(input);
+    let dir = manifest_dir();
+
+    let load = |path: LitStr, index| {
+        let path = dir.join(path.value());
+        lookup_in(index, &parse(&path)?, &file_name(&path))
+    };
+
+    load(a, index.clone())
+        .and_then(|a| difference(a, load(b, index)?))
+        .unwrap_or_else(SynError::into_compile_error)
+        .into()
+}
+
+/// Converts the strings of array `a` missing in array `b` to a `&[&str]`.
+fn difference(a: Value, b: Value) -> Result<TokenStream2, SynError> {
+    let b = strings(b)?;
+    let strings = strings(a)?.into_iter().filter(|s| !b.contains(s));
+    Ok(quote!(&[#(#strings),*]))
+}
+
+/// Parse name, version and description of the package from `Cargo.toml` at compile time.
+///
+/// Emits a `(name, version, description)` tuple of type `(&str, &str, &str)`, e.g. for `--version` output.
+/// A missing description is emitted as empty string.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_about;
+///
+/// let (name, version, description) = include_toml_about!();
+/// assert_eq!(name, "include-cargo-toml2");
+/// assert_eq!(version, "0.3.1");
+/// assert_eq!(description, "Load properties of Cargo.toml at compile time");
+/// ```
+#[proc_macro]
+pub fn include_toml_about(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as Nothing);
+
+    cargo_toml()
+        .and_then(|toml| about(&toml))
+        .unwrap_or_else(SynError::into_compile_error)
+        .into()
+}
+
+/// Converts name, version and description of `[package]` to a tuple.
+fn about(toml: &Value) -> Result<TokenStream2, SynError> {
+    let (name, version, description) = package_about(toml)?;
+    Ok(quote!((#name, #version, #description)))
+}
+
+/// Returns name, version and description of `[package]`, where the description defaults to an empty string.
+fn package_about(toml: &Value) -> Result<(String, String, String), SynError> {
+    let required = |key: &str| {
+        package_field(toml, key)
+            .ok_or_else(|| error(format!("Cannot find key \"{}\" in Cargo.toml", key)))
+            .and_then(string)
+    };
+    let description = package_field(toml, "description")
+        .map(string)
+        .transpose()?
+        .unwrap_or_default();
+    Ok((required("name")?, required("version")?, description))
+}
+
+/// Returns the value of `key` in `[package]`, if any.
+fn package_field(toml: &Value, key: &str) -> Option<Value> {
+    toml.get("package")
+        .and_then(|package| package.get(key))
+        .cloned()
+}
+
+/// Parse the package information used by [clap](https://crates.io/crates/clap) from `Cargo.toml` at compile time.
+///
+/// Emits a `(name, version, about, author)` tuple of type `(&'static str, &'static str, &'static str, &'static str)`,
+/// which can be passed to `Command::new`, `Command::version`, `Command::about` and `Command::author`.
+/// Missing descriptions and authors are emitted as empty strings, multiple authors are separated by `", "`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_clap;
+///
+/// let (name, version, about, author) = include_toml_clap!();
+/// let command = clap::Command::new(name)
+///     .version(version)
+///     .about(about)
+///     .author(author);
+/// assert_eq!(command.get_name(), "include-cargo-toml2");
+/// assert_eq!(command.get_version(), Some("0.3.1"));
+/// ```
+#[proc_macro]
+pub fn include_toml_clap(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as Nothing);
+
+    cargo_toml()
+        .and_then(|toml| clap(&toml))
+        .unwrap_or_else(SynError::into_compile_error)
+        .into()
+}
+
+/// Converts name, version, description and authors of `[package]` to a tuple.
+fn clap(toml: &Value) -> Result<TokenStream2, SynError> {
+    let (name, version, about) = package_about(toml)?;
+    let author = package_field(toml, "authors")
+        .map(strings)
+        .transpose()?
+        .unwrap_or_default()
+        .join(", ");
+    Ok(quote!((#name, #version, #about, #author)))
+}
+
+/// Struct that parses input of [`include_toml_when`] and [`include_toml_if`].
+///
+/// Input should consist of a [`TomlIndex`], a comma and tokens in braces.
+struct WhenInput {
+    index: TomlIndex,
+    body: TokenStream2,
+}
+
+impl Parse for WhenInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        input.parse::<Comma>()?;
+        let content;
+        braced!(content in input);
+        let body = content.parse()?;
+        Ok(Self { index, body })
+    }
+}
+
+/// Emit tokens depending on a flag in `Cargo.toml` at compile time.
+///
+/// Emits the tokens in braces if the value is truthy as in [`include_toml_truthy!`],
+/// and nothing if it is falsy or the key is missing. The tokens can be statements or items.
+/// Other lookup errors, like indexing into a scalar, cause a `compile_error!`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_when;
+///
+/// include_toml_when!("lib"."proc-macro", {
+///     fn is_proc_macro() -> bool {
+///         true
+///     }
+/// });
+/// include_toml_when!("package"."metadata"."missing", {
+///     compile_error!("Not emitted");
+/// });
+///
+/// assert!(is_proc_macro());
+/// ```
+#[proc_macro]
+pub fn include_toml_when(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: WhenInput, document| {
+        when(document.lookup_opt(input.index)?, input.body)
+    })
+}
+
+/// Returns `body` if `value` is present and truthy, and no tokens otherwise.
+fn when(value: Option<Value>, body: TokenStream2) -> Result<TokenStream2, SynError> {
+    match value.map(truthy).transpose()? {
+        Some(true) => Ok(body),
+        _ => Ok(TokenStream2::new()),
+    }
+}
+
+/// Struct that parses input of [`include_toml_static_assert_type`].
+///
+/// Input should consist of a [`TomlIndex`], a comma and a type.
+struct StaticAssertInput {
+    index: TomlIndex,
+    ty: Type,
+}
+
+impl Parse for StaticAssertInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        input.parse::<Comma>()?;
+        let ty = input.parse()?;
+        Ok(Self { index, ty })
+    }
+}
+
+/// Parse a value from `Cargo.toml` at compile time and assert its type.
+///
+/// Emits the value like [`include_toml!`] together with a constant that only compiles
+/// if the value has the given type, so changes of the manifest fail loudly.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_static_assert_type;
+///
+/// let version = include_toml_static_assert_type!("package"."version", &str);
+/// assert_eq!(version, "0.3.1");
+/// ```
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_static_assert_type;
+///
+/// // error: mismatched types
+/// let this_fails = include_toml_static_assert_type!("package"."keywords", &str);
+/// ```
+#[proc_macro]
+pub fn include_toml_static_assert_type(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: StaticAssertInput, document| {
+        let value = translate(document.lookup(input.index)?)?;
+        Ok(static_assert_type(&input.ty, value))
+    })
+}
+
+/// Emits `value` together with a constant asserting that `value` has type `ty`.
+fn static_assert_type(ty: &Type, value: TokenStream2) -> TokenStream2 {
+    quote! {{
+        const _: fn() = || {
+            let _: #ty = #value;
+        };
+        #value
+    }}
+}
+
+/// Parse a value from `[package.metadata]` of `Cargo.toml` at compile time.
+///
+/// The index is prefixed with `"package"."metadata"`, where tools usually store their settings.
+/// The value is emitted like [`include_toml!`].
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_metadata;
+///
+/// // [package.metadata.deb]
+/// // revision = "1"
+/// let revision = include_toml_metadata!(first ["tests/fixtures/metadata.toml"], "deb"."revision");
+/// assert_eq!(revision, "1");
+/// ```
+#[proc_macro]
+pub fn include_toml_metadata(input: TokenStream) -> TokenStream {
+    expand_with(input, |index: TomlIndex, document| {
+        translate(document.lookup(metadata(index))?)
+    })
+}
+
+/// Prefixes `index` with `"package"."metadata"`.
+fn metadata(index: TomlIndex) -> TomlIndex {
+    let prefix = ["package", "metadata"]
+        .iter()
+        .map(|key| Index::Str(key.to_string(), Span2::call_site()));
+    TomlIndex(prefix.chain(index.0).collect())
+}
+
+/// Parse a non-zero integer from `Cargo.toml` at compile time.
+///
+/// The selected value must be an integer in `1..=u32::MAX`, which is emitted as [`NonZeroU32`](core::num::NonZeroU32).
+/// The emitted expression can be used in constants.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_nonzero_u32;
+/// use std::num::NonZeroU32;
+///
+/// // [package.metadata]
+/// // workers = 4
+/// const WORKERS: NonZeroU32 = include_toml_nonzero_u32!(
+///     first ["tests/fixtures/metadata.toml"],
+///     "package"."metadata"."workers"
+/// );
+/// assert_eq!(WORKERS.get(), 4);
+/// ```
+#[proc_macro]
+pub fn include_toml_nonzero_u32(input: TokenStream) -> TokenStream {
+    expand(input, to_nonzero_u32)
+}
+
+/// Converts an integer in `1..=u32::MAX` to a [`NonZeroU32`](core::num::NonZeroU32) expression.
+fn to_nonzero_u32(value: Value) -> Result<TokenStream2, SynError> {
+    let int = integer(value)?;
+    let n = u32::try_from(int).ok().filter(|n| *n != 0).ok_or_else(|| {
+        error(format!(
+            "Expected integer in 1..={}, found {}",
+            u32::MAX,
+            int
+        ))
+    })?;
+    let n = Literal::u32_suffixed(n);
+    Ok(quote! {
+        match ::core::num::NonZeroU32::new(#n) {
+            ::core::option::Option::Some(n) => n,
+            ::core::option::Option::None => ::core::unreachable!(),
+        }
+    })
+}
+
+/// Parse an array from `Cargo.toml` at compile time and iterate over its elements.
+///
+/// Emits an iterator over the elements by value, which are emitted like [`include_toml!`].
+/// The array must not be empty and all elements must have the same Rust type.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_iter;
+///
+/// let length: usize = include_toml_iter!("package"."keywords").map(str::len).sum();
+/// assert_eq!(length, 39);
+/// ```
+#[proc_macro]
+pub fn include_toml_iter(input: TokenStream) -> TokenStream {
+    expand(input, iter)
+}
+
+/// Converts an array with elements of the same type to an iterator over a Rust array.
+fn iter(value: Value) -> Result<TokenStream2, SynError> {
+    let elements = array(value)?;
+    if elements.is_empty() {
+        return Err(error("Cannot infer element type of empty array"));
+    }
+    if !homogeneous(&elements) {
+        return Err(error("Expected array with elements of the same type"));
+    }
+    let elements = elements
+        .into_iter()
+        .map(translate)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(quote!(::core::iter::IntoIterator::into_iter([#(#elements),*])))
+}
+
+/// Struct that parses input of [`include_toml_cfg`].
+///
+/// Input should consist of a target operating system, an identifier and a [`TomlIndex`], separated by commas.
+struct CfgInput {
+    target_os: LitStr,
+    name: Ident,
+    index: TomlIndex,
+}
+
+impl Parse for CfgInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let target_os = input.parse()?;
+        input.parse::<Comma>()?;
+        let name = input.parse()?;
+        input.parse::<Comma>()?;
+        let index = input.parse()?;
+        Ok(Self {
+            target_os,
+            name,
+            index,
+        })
+    }
+}
+
+/// Parse a value from `Cargo.toml` at compile time as a constant for one target operating system.
+///
+/// Emits a constant item with the given name, which only exists if `target_os` matches.
+/// The value is emitted like [`include_toml!`] and the type of the constant is inferred from it.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_cfg;
+///
+/// include_toml_cfg!("linux", NAME, "package"."name");
+///
+/// #[cfg(target_os = "linux")]
+/// assert_eq!(NAME, "include-cargo-toml2");
+/// ```
+#[proc_macro]
+pub fn include_toml_cfg(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: CfgInput, document| {
+        target_item(&input.target_os, &input.name, document.lookup(input.index)?)
+    })
+}
+
+/// Emits a constant item named `name` holding `value`, which only exists for `target_os`.
+fn target_item(target_os: &LitStr, name: &Ident, value: Value) -> Result<TokenStream2, SynError> {
+    let item = const_item(name, value)?;
+    Ok(quote! {
+        #[cfg(target_os = #target_os)]
+        #item
+    })
+}
+
+/// Emits a constant item named `name` holding `value`.
+fn const_item(name: &Ident, value: Value) -> Result<TokenStream2, SynError> {
+    let ty = rust_type(&value);
+    let value = translate(value)?;
+    Ok(quote!(const #name: #ty = #value;))
+}
+
+/// Parse a path-like string from `Cargo.toml` at compile time as [`OsStr`](std::ffi::OsStr).
+///
+/// The selected value must be a non-empty string, which is emitted as `&'static OsStr`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_osstr;
+/// use std::path::Path;
+///
+/// let readme = Path::new(include_toml_osstr!("package"."readme"));
+/// assert_eq!(readme.extension().unwrap(), "md");
+/// ```
+#[proc_macro]
+pub fn include_toml_osstr(input: TokenStream) -> TokenStream {
+    expand(input, to_osstr)
+}
+
+/// Converts a non-empty string to an [`OsStr`](std::ffi::OsStr) expression.
+fn to_osstr(value: Value) -> Result<TokenStream2, SynError> {
+    let s = string(value)?;
+    if s.is_empty() {
+        return Err(error("Expected non-empty string"));
+    }
+    Ok(quote!(::std::ffi::OsStr::new(#s)))
+}
+
+/// Parse the number of scalars in a part of `Cargo.toml` at compile time.
+///
+/// Emits the number of strings, integers, floats, booleans and datetimes in the value as [`usize`],
+/// counting the elements of nested tables and arrays recursively.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_leaf_count;
+///
+/// assert_eq!(include_toml_leaf_count!("lib"), 1);
+/// assert_eq!(include_toml_leaf_count!("package"."keywords"), 5);
+/// ```
+#[proc_macro]
+pub fn include_toml_leaf_count(input: TokenStream) -> TokenStream {
+    expand(input, |value| {
+        Ok(Literal::usize_suffixed(leaf_count(&value)).to_token_stream())
+    })
+}
+
+/// Returns the number of scalars in `value`, including those of nested tables and arrays.
+fn leaf_count(value: &Value) -> usize {
+    match value {
+        Value::Array(a) => a.iter().map(leaf_count).sum(),
+        Value::Table(t) => t.values().map(leaf_count).sum(),
+        _ => 1,
+    }
+}
+
+/// Parse a value from `Cargo.toml` at compile time together with its index.
+///
+/// Emits a `(path, value)` tuple, where `path` is the index joined by dots as [`&str`],
+/// and `value` is emitted like [`include_toml!`].
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_labeled;
+///
+/// let (path, version) = include_toml_labeled!("package"."version");
+/// assert_eq!(format!("{} = {}", path, version), "package.version = 0.3.1");
+/// ```
+#[proc_macro]
+pub fn include_toml_labeled(input: TokenStream) -> TokenStream {
+    expand_with(input, |index: TomlIndex, document| {
+        let path = dotted(&index);
+        let value = translate(document.lookup(index)?)?;
+        Ok(quote!((#path, #value)))
+    })
+}
+
+/// Joins the items of `index` by dots.
+fn dotted(index: &TomlIndex) -> String {
+    index
+        .0
+        .iter()
+        .map(Index::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Parse a human-readable size from `Cargo.toml` as number of bytes at compile time.
+///
+/// The selected value must be a string like `"256MiB"`, which is emitted as [`u64`].
+/// Supported units are `B`, `KB`, `MB` and `GB` with powers of 1000, and `KiB`, `MiB` and `GiB` with powers of 1024.
+/// A number without unit is a number of bytes.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_bytes_size;
+///
+/// // [package.metadata]
+/// // cache-size = "256MiB"
+/// const CACHE_SIZE: u64 = include_toml_bytes_size!(first ["tests/fixtures/metadata.toml"], "package"."metadata"."cache-size");
+/// assert_eq!(CACHE_SIZE, 256 * 1024 * 1024);
+/// ```
+#[proc_macro]
+pub fn include_toml_bytes_size(input: TokenStream) -> TokenStream {
+    expand(input, |value| {
+        let bytes = bytes_size(&string(value)?)?;
+        Ok(Literal::u64_suffixed(bytes).to_token_stream())
+    })
+}
+
+/// Parses a size like `256MiB` to a number of bytes.
+fn bytes_size(size: &str) -> Result<u64, SynError> {
+    let invalid = || error(format!("Cannot parse \"{}\" as size in bytes", size));
+    let digits = size
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(digits);
+    let factor: u64 = match unit.trim_start() {
+        "" | "B" => 1,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        _ => return Err(invalid()),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(factor))
+        .ok_or_else(invalid)
+}
+
+/// Parse a string from `Cargo.toml` as C string at compile time.
+///
+/// The selected value must be a string without NUL bytes,
+/// which is emitted nul-terminated as `&'static CStr`. The emitted expression can be used in constants.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_cstr;
+/// use std::ffi::CStr;
+///
+/// const NAME: &CStr = include_toml_cstr!("package"."name");
+/// assert_eq!(NAME.to_bytes(), b"include-cargo-toml2");
+/// ```
+#[proc_macro]
+pub fn include_toml_cstr(input: TokenStream) -> TokenStream {
+    expand(input, to_cstr)
+}
+
+/// Converts a string without NUL bytes to a [`CStr`](core::ffi::CStr) expression.
+fn to_cstr(value: Value) -> Result<TokenStream2, SynError> {
+    let s = string(value)?;
+    if let Some(i) = s.find('\0') {
+        return Err(error(format!("String contains NUL byte at {}", i)));
+    }
+    let mut bytes = s.into_bytes();
+    bytes.push(0);
+    let bytes = Literal::byte_string(&bytes);
+    Ok(quote! {
+        match ::core::ffi::CStr::from_bytes_with_nul(#bytes) {
+            ::core::result::Result::Ok(s) => s,
+            ::core::result::Result::Err(_) => ::core::unreachable!(),
+        }
+    })
+}
+
+/// Parse a string from `Cargo.toml` in uppercase at compile time.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_upper;
+///
+/// assert_eq!(include_toml_upper!("package"."name"), "INCLUDE-CARGO-TOML2");
+/// ```
+#[proc_macro]
+pub fn include_toml_upper(input: TokenStream) -> TokenStream {
+    expand(input, |value| to_cased(value, str::to_uppercase))
+}
+
+/// Parse a string from `Cargo.toml` in lowercase at compile time.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_lower;
+///
+/// assert_eq!(include_toml_lower!("package"."keywords".2), "cargo-toml");
+/// ```
+#[proc_macro]
+pub fn include_toml_lower(input: TokenStream) -> TokenStream {
+    expand(input, |value| to_cased(value, str::to_lowercase))
+}
+
+/// Converts a string to a [`&str`] literal with the case changed by `case`.
+fn to_cased(value: Value, case: fn(&str) -> String) -> Result<TokenStream2, SynError> {
+    let s = case(&string(value)?);
+    Ok(quote!(#s))
+}
+
+/// Struct that parses input of [`include_toml_require`].
+///
+/// Input should consist of [`TomlIndex`]es in brackets, separated by commas.
+struct RequireInput(Vec<TomlIndex>);
+
+impl Parse for RequireInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let indexes = Punctuated::<TomlIndex, Comma>::parse_terminated_with(input, |input| {
+            let content;
+            bracketed!(content in input);
+            content.parse()
+        })?;
+        Ok(Self(indexes.into_iter().collect()))
+    }
+}
+
+/// Check that `Cargo.toml` contains required keys at compile time.
+///
+/// Every index in brackets must refer to a scalar, and strings must not be empty.
+/// Emits `()` if all keys are present, and a single `compile_error!` listing all missing or empty keys otherwise.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_require;
+///
+/// include_toml_require!(["package"."name"], ["package"."description"], ["package"."repository"]);
+/// ```
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_require;
+///
+/// // error: Missing required keys in Cargo.toml:
+/// //        - package.license: missing
+/// //        - package.keywords: not a scalar
+/// include_toml_require!(["package"."license"], ["package"."keywords"]);
+/// ```
+#[proc_macro]
+pub fn include_toml_require(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: RequireInput, document| {
+        require(input.0, &document.value)
+    })
+}
+
+/// Emits `()` if every index refers to a non-empty scalar, or an error listing all others.
+fn require(indexes: Vec<TomlIndex>, toml: &Value) -> Result<TokenStream2, SynError> {
+    let problems: Vec<String> = indexes
+        .into_iter()
+        .filter_map(|index| {
+            let path = dotted(&index);
+            let problem = match lookup(index, toml) {
+                Err(_) => "missing",
+                Ok(Value::String(ref s)) if s.is_empty() => "empty",
+                Ok(Value::Array(_)) | Ok(Value::Table(_)) => "not a scalar",
+                Ok(_) => return None,
+            };
+            Some(format!("- {}: {}", path, problem))
+        })
+        .collect();
+    if problems.is_empty() {
+        Ok(quote!(()))
+    } else {
+        Err(error(format!(
+            "Missing required keys in Cargo.toml:\n{}",
+            problems.join("\n")
+        )))
+    }
+}
+
+/// Parse a string from `Cargo.toml` as line at compile time.
+///
+/// Emits the string with a trailing `\n` as [`&str`], e.g. to concatenate generated files.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_line;
+///
+/// assert_eq!(include_toml_line!("package"."version"), "0.3.1\n");
+/// ```
+#[proc_macro]
+pub fn include_toml_line(input: TokenStream) -> TokenStream {
+    expand(input, to_line)
+}
+
+/// Converts a string to a [`&str`] literal ending with a newline.
+fn to_line(value: Value) -> Result<TokenStream2, SynError> {
+    let line = string(value)? + "\n";
+    Ok(quote!(#line))
+}
+
+/// Struct that parses input of [`include_toml_alias`].
+///
+/// Input should consist of a [`TomlIndex`] of the alias table, a comma and the alias.
+struct AliasInput {
+    table: TomlIndex,
+    alias: LitStr,
+}
+
+impl Parse for AliasInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let table = input.parse()?;
+        input.parse::<Comma>()?;
+        let alias = input.parse()?;
+        Ok(Self { table, alias })
+    }
+}
+
+/// Parse a value from `Cargo.toml` through an alias at compile time.
+///
+/// Looks up the alias in the given table, whose values are paths separated by dots like `"package.version"`.
+/// Items of the path consisting of digits index arrays.
+/// The value at the path is looked up from the root of `Cargo.toml` and emitted like [`include_toml!`].
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_alias;
+///
+/// // [package.metadata.aliases]
+/// // short = "package.metadata.deb.revision"
+/// let revision = include_toml_alias!(first ["tests/fixtures/metadata.toml"], "package"."metadata"."aliases", "short");
+/// assert_eq!(revision, "1");
+/// ```
+#[proc_macro]
+pub fn include_toml_alias(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: AliasInput, document| {
+        translate(alias(input.table, &input.alias, &document)?)
+    })
+}
+
+/// Looks up the path stored under `alias` in the table at `table`, and then the value at that path.
+fn alias(table: TomlIndex, alias: &LitStr, document: &Document) -> Result<Value, SynError> {
+    let aliases = document.lookup(table)?;
+    let path = aliases
+        .get(alias.value())
+        .cloned()
+        .ok_or_else(|| {
+            SynError::new(
+                alias.span(),
+                format!("Cannot find alias \"{}\"", alias.value()),
+            )
+        })
+        .and_then(string)?;
+    let index = TomlIndex(
+        path.split('.')
+            .map(|item| match item.parse() {
+                Ok(i) => Index::Int(i, alias.span()),
+                Err(_) => Index::Str(item.to_string(), alias.span()),
+            })
+            .collect(),
+    );
+    document.lookup(index).map_err(|e| {
+        SynError::new(
+            alias.span(),
+            format!("Alias \"{}\" refers to \"{}\": {}", alias.value(), path, e),
+        )
+    })
+}
+
+/// Parse a regular expression from `Cargo.toml` at compile time.
+///
+/// The selected value must be a string that is a valid pattern of the [regex](https://crates.io/crates/regex) crate,
+/// which is emitted as [`&str`]. Invalid patterns cause a `compile_error!` with the error of the regex crate.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_regex;
+///
+/// assert_eq!(include_toml_regex!("package"."version"), "0.3.1");
+/// ```
+///
+/// Requires the `include_toml_regex` feature, which is enabled by default.
+#[cfg(feature = "include_toml_regex")]
+#[proc_macro]
+pub fn include_toml_regex(input: TokenStream) -> TokenStream {
+    expand(input, to_regex)
+}
+
+/// Converts a string to a [`&str`] literal if it is a valid regular expression.
+#[cfg(feature = "include_toml_regex")]
+fn to_regex(value: Value) -> Result<TokenStream2, SynError> {
+    let pattern = string(value)?;
+    Regex::new(&pattern).map_err(|e| error(format!("Invalid regular expression: {}", e)))?;
+    Ok(quote!(#pattern))
+}
+
+/// Parse a boolean from `Cargo.toml` at compile time.
+///
+/// The selected value must be a boolean, which is emitted as [`bool`] literal.
+/// Unlike [`include_toml_truthy!`], other types are rejected, and the literal can be used as const generic argument.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_bool_const;
+///
+/// struct Switch<const ON: bool>;
+///
+/// impl<const ON: bool> Switch<ON> {
+///     fn on(&self) -> bool {
+///         ON
+///     }
+/// }
+///
+/// assert!(Switch::<{ include_toml_bool_const!("lib"."proc-macro") }>.on());
+/// ```
+#[proc_macro]
+pub fn include_toml_bool_const(input: TokenStream) -> TokenStream {
+    expand(input, to_bool)
+}
+
+/// Converts a boolean to a [`bool`] literal.
+fn to_bool(value: Value) -> Result<TokenStream2, SynError> {
+    match value {
+        Value::Boolean(b) => Ok(Lit::Bool(LitBool::new(b, Span2::call_site())).to_token_stream()),
+        other => Err(error(format!(
+            "Expected boolean, found {}",
+            other.type_str()
+        ))),
+    }
+}
+
+/// Struct that parses input of [`include_toml_join`].
+///
+/// Input should consist of a [`TomlIndex`], a comma and the delimiter.
+struct JoinInput {
+    index: TomlIndex,
+    delimiter: LitStr,
+}
+
+impl Parse for JoinInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        input.parse::<Comma>()?;
+        let delimiter = input.parse()?;
+        Ok(Self { index, delimiter })
+    }
+}
+
+/// Parse an array of strings from `Cargo.toml` as single string at compile time.
+///
+/// Emits the strings joined by the delimiter as [`&str`]. An empty array is emitted as empty string.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_join;
+///
+/// assert_eq!(
+///     include_toml_join!("package"."keywords", ", "),
+///     "macro, version, Cargo-toml, compile-time, parse"
+/// );
+/// ```
+#[proc_macro]
+pub fn include_toml_join(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: JoinInput, document| {
+        join(document.lookup(input.index)?, &input.delimiter.value())
+    })
+}
+
+/// Converts an array of strings to a [`&str`] literal of the strings joined by `delimiter`.
+fn join(value: Value, delimiter: &str) -> Result<TokenStream2, SynError> {
+    let joined = strings(value)?.join(delimiter);
+    Ok(quote!(#joined))
+}
+
+/// Parse a value from `Cargo.toml` selected by a JSON Pointer at compile time.
+///
+/// The pointer follows [RFC 6901](https://www.rfc-editor.org/rfc/rfc6901): it is either empty for the whole document,
+/// or consists of items each starting with `/`, where `~1` stands for `/` and `~0` for `~`.
+/// Items index arrays if they consist of digits. The value is emitted like [`include_toml!`].
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_pointer;
+///
+/// assert_eq!(include_toml_pointer!("/package/keywords/0"), "macro");
+/// assert_eq!(include_toml_pointer!("/lib/proc-macro"), true);
+/// ```
+#[proc_macro]
+pub fn include_toml_pointer(input: TokenStream) -> TokenStream {
+    expand_with(input, |pointer: LitStr, document| {
+        translate(follow(&pointer, &document)?)
+    })
+}
+
+/// Looks up the value `pointer` refers to in `document`.
+fn follow(pointer: &LitStr, document: &Document) -> Result<Value, SynError> {
+    let span = pointer.span();
+    let pointer = pointer.value();
+    if pointer.is_empty() {
+        return Ok(document.value.clone());
+    }
+    let items = match pointer.strip_prefix('/') {
+        Some(items) => items.split('/'),
+        None => return Err(SynError::new(span, "JSON Pointer must start with `/`")),
+    };
+    let mut value = &document.value;
+    for item in items {
+        if item.replace("~0", "").replace("~1", "").contains('~') {
+            return Err(SynError::new(
+                span,
+                format!("Invalid escape in JSON Pointer item \"{}\"", item),
+            ));
+        }
+        let key = item.replace("~1", "/").replace("~0", "~");
+        // RFC 6901 only allows `0` or digits without leading zeros as array index.
+        let index = match (value, key.parse()) {
+            (Value::Array(_), Ok(_)) if key.len() > 1 && key.starts_with('0') => {
+                return Err(SynError::new(
+                    span,
+                    format!("Array index \"{}\" in JSON Pointer has leading zeros", key),
+                ))
+            }
+            (Value::Array(_), Ok(i)) if key.bytes().all(|b| b.is_ascii_digit()) => {
+                Index::Int(i, span)
+            }
+            _ => Index::Str(key, span),
+        };
+        value = get(value, &index, &document.file)?;
+    }
+    Ok(value.clone())
+}
+
+/// Struct that parses input of [`include_toml_match`].
+///
+/// Input should consist of an expression, a [`TomlIndex`] of an array of tables,
+/// and the keys of names and handlers, separated by commas.
+struct MatchInput {
+    expr: Expr,
+    tables: TomlIndex,
+    name: LitStr,
+    handler: LitStr,
+}
+
+impl Parse for MatchInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let expr = input.parse()?;
+        input.parse::<Comma>()?;
+        let tables = input.parse()?;
+        input.parse::<Comma>()?;
+        let name = input.parse()?;
+        input.parse::<Comma>()?;
+        let handler = input.parse()?;
+        Ok(Self {
+            expr,
+            tables,
+            name,
+            handler,
+        })
+    }
+}
+
+/// Parse an array of tables from `Cargo.toml` as dispatch table at compile time.
+///
+/// Emits a `match` of the `&str` expression, with an arm for each table that maps the string under the name key
+/// to `Some(handler)`, where `handler` is the path stored under the handler key. Other strings are mapped to `None`.
+/// All handlers must have the same type, so functions should be coerced to function pointers by a type annotation.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_match;
+///
+/// mod commands {
+///     pub fn build() {}
+///     pub fn test() {}
+/// }
+///
+/// // [[commands]]
+/// // name = "build"
+/// // handler = "commands::build"
+/// let name = "build";
+/// let handler: Option<fn()> =
+///     include_toml_match!(first ["tests/fixtures/tables.toml"], name, "commands", "name", "handler");
+/// assert!(handler.is_some());
+/// ```
+#[proc_macro]
+pub fn include_toml_match(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: MatchInput, document| {
+        let tables = document.lookup(input.tables)?;
+        dispatch(
+            &input.expr,
+            tables,
+            &input.name.value(),
+            &input.handler.value(),
+        )
+    })
+}
+
+/// Emits a `match` of `expr` mapping the `name` of each table to `Some` of its `handler` path.
+fn dispatch(
+    expr: &Expr,
+    tables: Value,
+    name: &str,
+    handler: &str,
+) -> Result<TokenStream2, SynError> {
+    let mut names = Vec::new();
+    let mut handlers = Vec::new();
+    for (i, table) in array(tables)?.into_iter().enumerate() {
+        let field = |key: &str| {
+            table
+                .get(key)
+                .cloned()
+                .ok_or_else(|| error(format!("Cannot find key \"{}\" in element {}", key, i)))
+                .and_then(string)
+        };
+        let arm = field(name)?;
+        if names.contains(&arm) {
+            return Err(error(format!(
+                "Duplicate name \"{}\" in element {}",
+                arm, i
+            )));
+        }
+        let path: SynPath = syn::parse_str(&field(handler)?)
+            .map_err(|e| error(format!("Invalid handler in element {}: {}", i, e)))?;
+        names.push(arm);
+        handlers.push(path);
+    }
+    Ok(quote! {
+        match #expr {
+            #(#names => ::core::option::Option::Some(#handlers),)*
+            _ => ::core::option::Option::None,
+        }
+    })
+}
+
+/// Struct that parses input of [`include_toml_variant`].
+///
+/// Input should consist of the path of an enum with its variants in braces, a comma and a [`TomlIndex`].
+struct VariantInput {
+    ty: SynPath,
+    variants: Vec<Ident>,
+    index: TomlIndex,
+}
+
+impl Parse for VariantInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let ty = input.parse()?;
+        let content;
+        braced!(content in input);
+        let variants = Punctuated::<Ident, Comma>::parse_terminated(&content)?;
+        input.parse::<Comma>()?;
+        let index = input.parse()?;
+        Ok(Self {
+            ty,
+            variants: variants.into_iter().collect(),
+            index,
+        })
+    }
+}
+
+/// Parse a string from `Cargo.toml` as enum variant at compile time.
+///
+/// Macros cannot see the variants of an enum, so they are listed in braces after the path of the enum.
+/// The string must equal the name of one of them, ignoring ASCII case, and the variant is emitted as `Enum::Variant`.
+/// Other strings cause a `compile_error!` listing the valid variants.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_variant;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Keyword {
+///     Macro,
+///     Version,
+/// }
+///
+/// let keyword = include_toml_variant!(Keyword { Macro, Version }, "package"."keywords".1);
+/// assert_eq!(keyword, Keyword::Version);
+/// ```
+#[proc_macro]
+pub fn include_toml_variant(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: VariantInput, document| {
+        variant(&input.ty, &input.variants, document.lookup(input.index)?)
+    })
+}
+
+/// Emits the variant of `ty` whose name equals the string `value`, ignoring ASCII case.
+fn variant(ty: &SynPath, variants: &[Ident], value: Value) -> Result<TokenStream2, SynError> {
+    let name = string(value)?;
+    match variants
+        .iter()
+        .find(|variant| variant.to_string().eq_ignore_ascii_case(&name))
+    {
+        Some(variant) => Ok(quote!(#ty::#variant)),
+        None => Err(error(format!(
+            "Unknown variant \"{}\", expected one of: {}",
+            name,
+            variants
+                .iter()
+                .map(Ident::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+/// Parse a string from `Cargo.toml` without surrounding quotes at compile time.
+///
+/// If the string starts and ends with `"`, one layer of quotes is removed, e.g. for `token = "\"secret\""`.
+/// Other strings are emitted unchanged as [`&str`].
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_unquote;
+///
+/// assert_eq!(include_toml_unquote!("package"."name"), "include-cargo-toml2");
+/// ```
+#[proc_macro]
+pub fn include_toml_unquote(input: TokenStream) -> TokenStream {
+    expand(input, |value| {
+        let s = string(value)?;
+        let unquoted = unquote(&s);
+        Ok(quote!(#unquoted))
+    })
+}
+
+/// Removes one layer of surrounding double quotes from `s`, if any.
+fn unquote(s: &str) -> &str {
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+/// Struct that parses input of [`include_toml_fixed_len`].
+///
+/// Input should consist of a [`TomlIndex`], a comma and the length in bytes.
+struct FixedLenInput {
+    index: TomlIndex,
+    len: usize,
+}
+
+impl Parse for FixedLenInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        input.parse::<Comma>()?;
+        let len = input.parse::<LitInt>()?.base10_parse()?;
+        Ok(Self { index, len })
+    }
+}
+
+/// Parse a string of fixed length from `Cargo.toml` at compile time.
+///
+/// Emits the string as [`&str`] if it has exactly the given length in bytes, e.g. for currency or country codes.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_fixed_len;
+///
+/// assert_eq!(include_toml_fixed_len!("package"."edition", 4), "2018");
+/// ```
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_fixed_len;
+///
+/// // error: Expected string of 2 bytes, found 4 bytes
+/// let this_fails = include_toml_fixed_len!("package"."edition", 2);
+/// ```
+#[proc_macro]
+pub fn include_toml_fixed_len(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: FixedLenInput, document| {
+        fixed_len(document.lookup(input.index)?, input.len)
+    })
+}
+
+/// Converts a string of `len` bytes to a [`&str`] literal.
+fn fixed_len(value: Value, len: usize) -> Result<TokenStream2, SynError> {
+    let s = string(value)?;
+    if s.len() != len {
+        return Err(error(format!(
+            "Expected string of {} bytes, found {} bytes",
+            len,
+            s.len()
+        )));
+    }
+    Ok(quote!(#s))
+}
+
+/// Parse owner and name of the GitHub repository from `Cargo.toml` at compile time.
+///
+/// Reads `package.repository`, which must be a URL like `https://github.com/owner/repo`,
+/// optionally with `.git` suffix, and emits an `(owner, repo)` tuple of type `(&str, &str)`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_gh_repo;
+///
+/// assert_eq!(include_toml_gh_repo!(), ("slimboyfat", "include-cargo-toml2"));
+/// ```
+#[proc_macro]
+pub fn include_toml_gh_repo(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as Nothing);
+
+    cargo_toml()
+        .and_then(|toml| {
+            package_field(&toml, "repository")
+                .ok_or_else(|| error("Cannot find key \"repository\" in Cargo.toml"))
+                .and_then(string)
+        })
+        .and_then(|url| {
+            let (owner, repo) = gh_repo(&url)?;
+            Ok(quote!((#owner, #repo)))
+        })
+        .unwrap_or_else(SynError::into_compile_error)
+        .into()
+}
+
+/// Splits a GitHub repository URL into owner and name.
+fn gh_repo(url: &str) -> Result<(&str, &str), SynError> {
+    let path = [
+        "https://github.com/",
+        "http://github.com/",
+        "https://www.github.com/",
+    ]
+    .iter()
+    .find_map(|prefix| url.strip_prefix(prefix))
+    .ok_or_else(|| error(format!("Expected GitHub repository URL, found \"{}\"", url)))?;
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    match path.split('/').collect::<Vec<_>>()[..] {
+        [owner, repo] if !owner.is_empty() && !repo.is_empty() => Ok((owner, repo)),
+        _ => Err(error(format!(
+            "Expected owner and name in GitHub repository URL, found \"{}\"",
+            url
+        ))),
+    }
+}
+
+/// Struct that parses input of [`include_toml_const_def`], [`include_toml_spread_consts`]
+/// and [`include_toml_documented_const`].
+///
+/// Input should consist of an optional `allow_unused` modifier, an identifier, a comma and a [`TomlIndex`].
+struct ConstDefInput {
+    /// Set by the `allow_unused` modifier, which adds `#[allow(dead_code)]` to the emitted items.
+    allow_unused: bool,
+    name: Ident,
+    index: TomlIndex,
+}
+
+impl Parse for ConstDefInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let allow_unused = if input.peek2(Ident) {
+            let modifier: Ident = input.parse()?;
+            if modifier != "allow_unused" {
+                return Err(SynError::new(
+                    modifier.span(),
+                    format!("Unknown modifier `{}`, expected `allow_unused`", modifier),
+                ));
+            }
+            true
+        } else {
+            false
+        };
+        let name = input.parse()?;
+        input.parse::<Comma>()?;
+        let index = input.parse()?;
+        Ok(Self {
+            allow_unused,
+            name,
+            index,
+        })
+    }
+}
+
+impl ConstDefInput {
+    /// Returns the attributes of the emitted items.
+    fn attributes(&self) -> TokenStream2 {
+        if self.allow_unused {
+            quote!(#[allow(dead_code)])
+        } else {
+            TokenStream2::new()
+        }
+    }
+}
+
+/// Parse a value from `Cargo.toml` at compile time as public constant.
+///
+/// Emits a `pub const` item with the given name, e.g. at module scope.
+/// The value is emitted like [`include_toml!`] and the type of the constant is inferred from it.
+/// The `allow_unused` modifier adds `#[allow(dead_code)]`, so unused constants do not cause warnings.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_const_def;
+///
+/// include_toml_const_def!(VERSION, "package"."version");
+///
+/// assert_eq!(VERSION, "0.3.1");
+/// ```
+#[proc_macro]
+pub fn include_toml_const_def(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: ConstDefInput, document| {
+        let attributes = input.attributes();
+        let item = const_item(&input.name, document.lookup(input.index)?)?;
+        Ok(quote!(#attributes pub #item))
+    })
+}
+
+/// Parse a table of integers from `Cargo.toml` as sorted slice of pairs at compile time.
+///
+/// Emits a `&[(&str, i64)]` slice of the keys and values sorted by key, e.g. for [`slice::binary_search_by_key`].
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_int_pairs;
+///
+/// // [package.metadata.codes]
+/// // ok = 0
+/// // not-found = 404
+/// let codes: &[(&str, i64)] = include_toml_int_pairs!(first ["tests/fixtures/metadata.toml"], "package"."metadata"."codes");
+/// let code = codes.binary_search_by_key(&"ok", |(key, _)| key).map(|i| codes[i].1);
+/// assert_eq!(code, Ok(0));
+/// ```
+#[proc_macro]
+pub fn include_toml_int_pairs(input: TokenStream) -> TokenStream {
+    expand(input, to_int_pairs)
+}
+
+/// Converts a table of integers to a `&[(&str, i64)]` sorted by key.
+fn to_int_pairs(value: Value) -> Result<TokenStream2, SynError> {
+    let (keys, ints) = int_pairs(value)?;
+    Ok(quote!(&[#((#keys, #ints)),*]))
+}
+
+/// Returns the keys and the integer literals of a table of integers, sorted by key.
+fn int_pairs(value: Value) -> Result<(Vec<String>, Vec<Literal>), SynError> {
+    let mut pairs = table(value)?
+        .into_iter()
+        .map(|(key, value)| {
+            let int = integer(value).map_err(|e| error(format!("Key \"{}\": {}", key, e)))?;
+            Ok((key, int))
+        })
+        .collect::<Result<Vec<_>, SynError>>()?;
+    pairs.sort();
+    Ok(pairs
+        .into_iter()
+        .map(|(key, int)| (key, Literal::i64_suffixed(int)))
+        .unzip())
+}
+
+/// Struct that parses input of [`include_toml_mod`].
+///
+/// Input should consist of the module name, a comma and fields `name: index` in braces, separated by commas.
+struct ModInput {
+    name: Ident,
+    fields: Vec<(Ident, TomlIndex)>,
+}
+
+impl Parse for ModInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let name = input.parse()?;
+        input.parse::<Comma>()?;
+        let content;
+        braced!(content in input);
+        let fields =
+            Punctuated::<(Ident, TomlIndex), Comma>::parse_terminated_with(&content, |input| {
+                let field = input.parse()?;
+                input.parse::<Colon>()?;
+                Ok((field, input.parse()?))
+            })?;
+        Ok(Self {
+            name,
+            fields: fields.into_iter().collect(),
+        })
+    }
+}
+
+/// Parse several values from `Cargo.toml` at compile time as constants of a module.
+///
+/// Emits a `pub mod` with a `pub const` for each field, whose name is the field name in uppercase.
+/// The values are emitted like [`include_toml!`] and the types of the constants are inferred from them.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_mod;
+///
+/// include_toml_mod!(manifest, {
+///     name: "package"."name",
+///     version: "package"."version",
+/// });
+///
+/// assert_eq!(manifest::NAME, "include-cargo-toml2");
+/// assert_eq!(manifest::VERSION, "0.3.1");
+/// ```
+#[proc_macro]
+pub fn include_toml_mod(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: ModInput, document| {
+        let items = input
+            .fields
+            .into_iter()
+            .map(|(field, index)| {
+                let name = const_name(&field)?;
+                let item = const_item(&name, document.lookup(index)?)?;
+                Ok(quote!(pub #item))
+            })
+            .collect::<Result<Vec<_>, SynError>>()?;
+        let name = input.name;
+        Ok(quote! {
+            pub mod #name {
+                #(#items)*
+            }
+        })
+    })
+}
+
+/// Returns the uppercase constant name for `field`, which may be a raw identifier like `r#type`.
+fn const_name(field: &Ident) -> Result<Ident, SynError> {
+    let name = field.unraw().to_string().to_uppercase();
+    let mut ident = syn::parse_str::<Ident>(&name).map_err(|_| {
+        SynError::new(
+            field.span(),
+            format!("Cannot use `{}` as constant name", name),
+        )
+    })?;
+    ident.set_span(field.span());
+    Ok(ident)
+}
+
+/// Struct that parses input of [`include_toml_hex`].
+///
+/// Input should consist of a [`TomlIndex`], optionally followed by a comma and the number of digits.
+struct HexInput {
+    index: TomlIndex,
+    width: usize,
+}
+
+impl Parse for HexInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        let width = if input.parse::<Comma>().is_ok() {
+            input.parse::<LitInt>()?.base10_parse()?
+        } else {
+            0
+        };
+        Ok(Self { index, width })
+    }
+}
+
+/// Parse an integer from `Cargo.toml` as hexadecimal string at compile time.
+///
+/// The selected value must be a non-negative integer, which is emitted like `"0x1F"` as [`&str`].
+/// An optional number of digits pads the string with leading zeros.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_hex;
+///
+/// // [package.metadata]
+/// // magic = 31
+/// const MAGIC: &str = include_toml_hex!(first ["tests/fixtures/metadata.toml"], "package"."metadata"."magic");
+/// const PADDED: &str = include_toml_hex!(first ["tests/fixtures/metadata.toml"], "package"."metadata"."magic", 4);
+/// assert_eq!(MAGIC, "0x1F");
+/// assert_eq!(PADDED, "0x001F");
+/// ```
+#[proc_macro]
+pub fn include_toml_hex(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: HexInput, document| {
+        let hex = hex(document.lookup(input.index)?, input.width)?;
+        Ok(quote!(#hex))
+    })
+}
+
+/// Formats a non-negative integer as hexadecimal with at least `width` digits.
+fn hex(value: Value, width: usize) -> Result<String, SynError> {
+    match integer(value)? {
+        int if int < 0 => Err(error(format!(
+            "Expected non-negative integer, found {}",
+            int
+        ))),
+        int => Ok(format!("0x{:01$X}", int, width)),
+    }
+}
+
+/// Struct that parses input of [`include_toml_parse_fn`].
+///
+/// Input should consist of the function name, a comma and a [`TomlIndex`].
+struct ParseFnInput {
+    name: Ident,
+    index: TomlIndex,
+}
+
+impl Parse for ParseFnInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let name = input.parse()?;
+        input.parse::<Comma>()?;
+        let index = input.parse()?;
+        Ok(Self { name, index })
+    }
+}
+
+/// Parse a table of integers from `Cargo.toml` at compile time as function.
+///
+/// Emits a function item `fn name(s: &str) -> Option<i64>` with the given name,
+/// which returns the integer of key `s`, or `None` for other strings.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_parse_fn;
+///
+/// // [levels]
+/// // info = 1
+/// // error = 3
+/// include_toml_parse_fn!(first ["tests/fixtures/tables.toml"], parse_level, "levels");
+///
+/// assert_eq!(parse_level("error"), Some(3));
+/// assert_eq!(parse_level("trace"), None);
+/// ```
+#[proc_macro]
+pub fn include_toml_parse_fn(input: TokenStream) -> TokenStream {
+    expand_with(input, |ParseFnInput { name, index }, document| {
+        parse_fn(&name, document.lookup(index)?)
+    })
+}
+
+/// Emits a function named `name` matching the keys of a table of integers.
+fn parse_fn(name: &Ident, value: Value) -> Result<TokenStream2, SynError> {
+    let (keys, ints) = int_pairs(value)?;
+    Ok(quote! {
+        fn #name(s: &str) -> ::core::option::Option<i64> {
+            match s {
+                #(#keys => ::core::option::Option::Some(#ints),)*
+                _ => ::core::option::Option::None,
+            }
+        }
+    })
+}
+
+/// Struct that parses input of [`include_toml_assert_env`].
+///
+/// Input should consist of a [`TomlIndex`], a comma and the name of an environment variable.
+struct AssertEnvInput {
+    index: TomlIndex,
+    name: LitStr,
+}
+
+impl Parse for AssertEnvInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        input.parse::<Comma>()?;
+        let name = input.parse()?;
+        Ok(Self { index, name })
+    }
+}
+
+/// Check that a value of `Cargo.toml` equals an environment variable at compile time.
+///
+/// The value must be a scalar and is compared by its string form, so `"package"."version"`
+/// can be checked against a release tag in CI builds.
+/// Emits the value as string literal if both are equal,
+/// and a `compile_error!` if they differ or the environment variable is not set.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_assert_env;
+///
+/// let version = include_toml_assert_env!("package"."version", "CARGO_PKG_VERSION");
+/// assert_eq!(version, "0.3.1");
+/// ```
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_assert_env;
+///
+/// // error: Environment variable `INCLUDE_CARGO_TOML2_UNSET` is not set
+/// include_toml_assert_env!("package"."version", "INCLUDE_CARGO_TOML2_UNSET");
+/// ```
+#[proc_macro]
+pub fn include_toml_assert_env(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: AssertEnvInput, document| {
+        let name = input.name.value();
+        assert_env(document.lookup(input.index)?, &name, var(&name).ok())
+    })
+}
+
+/// Emits `value` as string literal if it equals `env`, the value of the environment variable `name`.
+fn assert_env(value: Value, name: &str, env: Option<String>) -> Result<TokenStream2, SynError> {
+    let value = scalar_string(value)?;
+    match env {
+        Some(env) if env == value => Ok(value.to_token_stream()),
+        Some(env) => Err(error(format!(
+            "Value `{}` does not match environment variable `{}` with value `{}`",
+            value, name, env
+        ))),
+        None => Err(error(format!("Environment variable `{}` is not set", name))),
+    }
+}
+
+/// Parse an array from `Cargo.toml` at compile time as one constant per element.
+///
+/// Emits a `const` item named `<NAME>_<i>` for the element at position `i`.
+/// The type of every constant is inferred from its element, so the array may be heterogeneous.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_spread_consts;
+///
+/// include_toml_spread_consts!(KEYWORD, "package"."keywords");
+///
+/// assert_eq!(KEYWORD_0, "macro");
+/// assert_eq!(KEYWORD_1, "version");
+/// ```
+#[proc_macro]
+pub fn include_toml_spread_consts(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: ConstDefInput, document| {
+        let attributes = input.attributes();
+        spread_consts(&attributes, &input.name, document.lookup(input.index)?)
+    })
+}
+
+/// Emits a constant item `<name>_<i>` with `attributes` for each element `i` of array `value`.
+fn spread_consts(
+    attributes: &TokenStream2,
+    name: &Ident,
+    value: Value,
+) -> Result<TokenStream2, SynError> {
+    array(value)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, element)| {
+            let item = const_item(
+                &Ident::new(&format!("{}_{}", name, i), name.span()),
+                element,
+            )?;
+            Ok(quote!(#attributes #item))
+        })
+        .collect()
+}
+
+/// Parse a string from `Cargo.toml` at compile time as [`Cow`](std::borrow::Cow).
+///
+/// The selected value must be a string, which is emitted as `Cow::Borrowed` of type `Cow<'static, str>`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_cow;
+/// use std::borrow::Cow;
+///
+/// let name: Cow<'static, str> = include_toml_cow!("package"."name");
+/// assert_eq!(name, Cow::Borrowed("include-cargo-toml2"));
+/// ```
+#[proc_macro]
+pub fn include_toml_cow(input: TokenStream) -> TokenStream {
+    expand(input, to_cow)
+}
+
+/// Converts a string to a `Cow::Borrowed` expression.
+fn to_cow(value: Value) -> Result<TokenStream2, SynError> {
+    let s = string(value)?;
+    Ok(quote!(::std::borrow::Cow::<'static, str>::Borrowed(#s)))
+}
+
+/// Check that the version of the package is a stable release at compile time.
+///
+/// Parses `package.version` of `Cargo.toml` as semantic version and fails with a `compile_error!`
+/// if it has a pre-release or build metadata component. Emits `()` otherwise.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_assert_stable;
+///
+/// include_toml_assert_stable!();
+/// ```
+#[proc_macro]
+pub fn include_toml_assert_stable(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as Nothing);
+
+    cargo_toml()
+        .and_then(|toml| assert_stable(&toml))
+        .unwrap_or_else(SynError::into_compile_error)
+        .into()
+}
+
+/// Emits `()` if the version of `[package]` is a stable release.
+fn assert_stable(toml: &Value) -> Result<TokenStream2, SynError> {
+    let version = package_field(toml, "version")
+        .ok_or_else(|| error("Cannot find key \"version\" in Cargo.toml"))
+        .and_then(string)?;
+    stable(&version)?;
+    Ok(quote!(()))
+}
+
+/// Returns an error if `version` is not a semantic version without pre-release and build metadata.
+fn stable(version: &str) -> Result<(), SynError> {
+    let (rest, build) = match version.split_once('+') {
+        Some((rest, build)) => (rest, Some(build)),
+        None => (version, None),
+    };
+    let (core, pre) = match rest.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (rest, None),
+    };
+    let numbers: Vec<&str> = core.split('.').collect();
+    if numbers.len() != 3
+        || numbers
+            .iter()
+            .any(|n| n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return Err(error(format!(
+            "Cannot parse version `{}` as semantic version",
+            version
+        )));
+    }
+    match (pre, build) {
+        (Some(pre), _) => Err(error(format!(
+            "Version `{}` is a pre-release `{}`, expected stable release",
+            version, pre
+        ))),
+        (None, Some(build)) => Err(error(format!(
+            "Version `{}` has build metadata `{}`, expected stable release",
+            version, build
+        ))),
+        (None, None) => Ok(()),
+    }
+}
+
+/// Parse the sum of a table of integers from `Cargo.toml` at compile time.
+///
+/// Emits the sum of the values of the table as [`i64`], and fails if a value is not an integer or the sum overflows.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_sum;
+///
+/// // [package.metadata.weights]
+/// // core = 3
+/// // extra = 2
+/// let total: i64 = include_toml_sum!(first ["tests/fixtures/metadata.toml"], "package"."metadata"."weights");
+/// assert_eq!(total, 5);
+/// ```
+#[proc_macro]
+pub fn include_toml_sum(input: TokenStream) -> TokenStream {
+    expand(input, to_sum)
+}
+
+/// Converts a table of integers to the sum of its values.
+fn to_sum(value: Value) -> Result<TokenStream2, SynError> {
+    let sum = table(value)?
+        .into_iter()
+        .try_fold(0i64, |sum, (key, value)| {
+            let int = integer(value).map_err(|e| error(format!("Key \"{}\": {}", key, e)))?;
+            sum.checked_add(int)
+                .ok_or_else(|| error("Sum of table overflows i64"))
+        })?;
+    Ok(Lit::new(Literal::i64_suffixed(sum)).to_token_stream())
+}
+
+/// Parse a value from `Cargo.toml` at compile time with `'static` type.
+///
+/// Emits the value like [`include_toml!`], but typed through a constant whose type is inferred from the value,
+/// so every string, also in nested tuples, is a `&'static str` and the result can initialize a `static` item.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_static;
+///
+/// static KEYWORDS: (&str, &str, &str, &str, &str) = include_toml_static!("package"."keywords");
+/// static LIB: ((&str, bool),) = include_toml_static!("lib");
+///
+/// assert_eq!(KEYWORDS.0, "macro");
+/// assert_eq!(LIB, (("proc-macro", true),));
+/// ```
+#[proc_macro]
+pub fn include_toml_static(input: TokenStream) -> TokenStream {
+    expand(input, to_static)
+}
+
+/// Emits `value` through a constant of type [`rust_type`].
+fn to_static(value: Value) -> Result<TokenStream2, SynError> {
+    let item = const_item(&Ident::new("VALUE", Span2::call_site()), value)?;
+    Ok(quote!({
+        #item
+        VALUE
+    }))
+}
+
+/// Parse a base64 encoded string from `Cargo.toml` at compile time.
+///
+/// The selected value must be a string in the standard base64 alphabet with optional padding,
+/// which is decoded and emitted as a `[u8; N]` array literal.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_base64;
+///
+/// // [package.metadata]
+/// // key = "aGVsbG8="
+/// const KEY: [u8; 5] = include_toml_base64!(first ["tests/fixtures/metadata.toml"], "package"."metadata"."key");
+/// assert_eq!(&KEY, b"hello");
+/// ```
+#[proc_macro]
+pub fn include_toml_base64(input: TokenStream) -> TokenStream {
+    expand(input, to_base64_bytes)
+}
+
+/// Converts a base64 encoded string to a `[u8; N]` literal of the decoded bytes.
+fn to_base64_bytes(value: Value) -> Result<TokenStream2, SynError> {
+    let bytes = base64(&string(value)?)?
+        .into_iter()
+        .map(Literal::u8_suffixed);
+    Ok(quote! ([#(#bytes),*]))
+}
+
+/// Decodes `s` in the standard base64 alphabet, where padding with `=` is optional.
+fn base64(s: &str) -> Result<Vec<u8>, SynError> {
+    let data = s.trim_end_matches('=');
+    let padding = s.len() - data.len();
+    if data.len() % 4 == 1 || padding > 2 || (padding > 0 && s.len() % 4 != 0) {
+        return Err(error(format!("Invalid length of base64 string \"{}\"", s)));
+    }
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for (i, c) in data.chars().enumerate() {
+        let sextet = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' => 62,
+            '/' => 63,
+            _ => {
+                return Err(error(format!(
+                    "Invalid base64 character '{}' at position {}",
+                    c, i
+                )))
+            }
+        };
+        buffer = buffer << 6 | sextet;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Parse the email address of an author from `Cargo.toml` at compile time.
+///
+/// The integer selects an element of `package.authors`, which must have the form `Name <email>`.
+/// The email address in angle brackets is checked to look like `local@domain.tld` and emitted as `&str`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_author_email;
+///
+/// assert_eq!(include_toml_author_email!(0), "s1im80yf4t@gmail.com");
+/// ```
+#[proc_macro]
+pub fn include_toml_author_email(input: TokenStream) -> TokenStream {
+    let index = parse_macro_input!(input as LitInt);
+
+    cargo_toml()
+        .and_then(|toml| author_email(&toml, &index))
+        .unwrap_or_else(SynError::into_compile_error)
+        .into()
+}
+
+/// Emits the email address of the author at `index` of `[package]`.
+fn author_email(toml: &Value, index: &LitInt) -> Result<TokenStream2, SynError> {
+    let authors = package_field(toml, "authors")
+        .ok_or_else(|| error("Cannot find key \"authors\" in Cargo.toml"))?;
+    let author = get(
+        &authors,
+        &Index::Int(index.base10_parse()?, index.span()),
+        "Cargo.toml",
+    )
+    .cloned()
+    .and_then(string)?;
+    let email = email(&author)?;
+    Ok(quote!(#email))
+}
+
+/// Returns the email address in angle brackets at the end of `author`.
+fn email(author: &str) -> Result<&str, SynError> {
+    let email = author
+        .strip_suffix('>')
+        .and_then(|rest| rest.rsplit_once('<'))
+        .map(|(_, email)| email)
+        .ok_or_else(|| error(format!("Author \"{}\" has no email address", author)))?;
+    let valid = match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.split('.').count() > 1
+                && domain.split('.').all(|label| !label.is_empty())
+                && !email.contains(char::is_whitespace)
+        }
+        None => false,
+    };
+    if valid {
+        Ok(email)
+    } else {
+        Err(error(format!(
+            "Invalid email address \"{}\" of author \"{}\"",
+            email, author
+        )))
+    }
+}
+
+/// Parse a percentage from `Cargo.toml` at compile time.
+///
+/// The selected value must be a string of a number followed by `%`, like `"75%"`,
+/// which is emitted as fraction of type [`f64`], like `0.75f64`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_percent;
+///
+/// // [package.metadata]
+/// // opacity = "75%"
+/// assert_eq!(include_toml_percent!(first ["tests/fixtures/metadata.toml"], "package"."metadata"."opacity"), 0.75);
+/// ```
+#[proc_macro]
+pub fn include_toml_percent(input: TokenStream) -> TokenStream {
+    expand(input, to_percent)
+}
+
+/// Converts a percentage string to a fraction.
+fn to_percent(value: Value) -> Result<TokenStream2, SynError> {
+    let s = string(value)?;
+    let percent = s
+        .strip_suffix('%')
+        .and_then(|number| number.trim().parse::<f64>().ok())
+        .filter(|percent| percent.is_finite())
+        .ok_or_else(|| error(format!("Expected percentage like \"75%\", found \"{}\"", s)))?;
+    Ok(Lit::new(Literal::f64_suffixed(percent / 100.0)).to_token_stream())
+}
+
+/// Parse a value from `Cargo.toml` at compile time as documented public constant.
+///
+/// Emits a `pub const` item like [`include_toml_const_def!`] with a doc comment naming the path of the value,
+/// like `/// Value of package.name from Cargo.toml`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_documented_const;
+///
+/// include_toml_documented_const!(NAME, "package"."name");
+///
+/// assert_eq!(NAME, "include-cargo-toml2");
+/// ```
+#[proc_macro]
+pub fn include_toml_documented_const(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: ConstDefInput, document| {
+        let attributes = input.attributes();
+        let path = dotted(&input.index);
+        let item = documented_const(&input.name, &path, document.lookup(input.index)?)?;
+        Ok(quote!(#attributes #item))
+    })
+}
+
+/// Emits a public constant item named `name` holding `value`, documented with `path`.
+fn documented_const(name: &Ident, path: &str, value: Value) -> Result<TokenStream2, SynError> {
+    let doc = format!(" Value of {} from Cargo.toml", path);
+    let item = const_item(name, value)?;
+    Ok(quote! {
+        #[doc = #doc]
+        pub #item
+    })
+}
+
+/// Parse an array from `Cargo.toml` at compile time in reverse order.
+///
+/// The elements are emitted like [`include_toml!`], i.e. as tuple, or as Rust array
+/// for homogeneous arrays if `array_mode` is set in `[package.metadata.include-cargo-toml2]`.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_rev;
+///
+/// assert_eq!(
+///     include_toml_rev!("package"."keywords"),
+///     ("parse", "compile-time", "Cargo-toml", "version", "macro")
+/// );
+/// ```
+#[proc_macro]
+pub fn include_toml_rev(input: TokenStream) -> TokenStream {
+    expand_with(input, |index: TomlIndex, document| {
+        let options = Options {
+            arrays: array_mode(&cargo_toml()?)?,
+            ..Options::default()
+        };
+        rev(document.lookup(index)?, &options)
+    })
+}
+
+/// Converts array `value` with the elements in reverse order.
+fn rev(value: Value, options: &Options) -> Result<TokenStream2, SynError> {
+    let mut elements = array(value)?;
+    elements.reverse();
+    translate_with(Value::Array(elements), options)
+}
+
+/// Struct that parses input of [`include_toml_version_ge`].
+///
+/// Input should consist of a [`TomlIndex`], a comma and a version.
+struct VersionGeInput {
+    index: TomlIndex,
+    version: LitStr,
+}
+
+impl Parse for VersionGeInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        input.parse::<Comma>()?;
+        let version = input.parse()?;
+        Ok(Self { index, version })
+    }
+}
+
+/// Compare a version from `Cargo.toml` with a given version at compile time.
+///
+/// Emits `true` if the version in `Cargo.toml` is greater than or equal to the given version, and `false` otherwise.
+/// Both are compared as semantic versions, where missing minor and patch numbers count as `0`,
+/// like in `rust-version = "1.70"`, and pre-releases are lower than their release.
+/// A missing key is an error, so a package without `rust-version` does not silently enable code.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_version_ge;
+///
+/// const NEWER: bool = include_toml_version_ge!("package"."version", "0.3");
+/// assert!(NEWER);
+/// assert!(!include_toml_version_ge!("package"."version", "1.0.0"));
+/// ```
+#[proc_macro]
+pub fn include_toml_version_ge(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: VersionGeInput, document| {
+        let version = string(document.lookup(input.index)?)?;
+        let ge = version_ge(&version, &input.version.value())?;
+        Ok(Lit::Bool(LitBool::new(ge, Span2::call_site())).to_token_stream())
+    })
+}
+
+/// Returns whether `version` is greater than or equal to `minimum`.
+fn version_ge(version: &str, minimum: &str) -> Result<bool, SynError> {
+    Ok(semver(version)? >= semver(minimum)?)
+}
+
+/// Parses `version` to major, minor and patch number and whether it is a release.
+///
+/// Build metadata is ignored, missing minor and patch numbers are `0`.
+fn semver(version: &str) -> Result<([u64; 3], bool), SynError> {
+    let invalid = || {
+        error(format!(
+            "Cannot parse version `{}` as semantic version",
+            version
+        ))
+    };
+    let core = version.split('+').next().unwrap_or_default();
+    let (core, release) = match core.split_once('-') {
+        Some((core, _)) => (core, false),
+        None => (core, true),
+    };
+    let mut numbers = [0; 3];
+    let parts: Vec<&str> = core.split('.').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    for (number, part) in numbers.iter_mut().zip(parts) {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        *number = part.parse().map_err(|_| invalid())?;
+    }
+    Ok((numbers, release))
+}
+
+/// Struct that parses input of [`include_toml_fmt`].
+///
+/// Input should consist of a format string and [`TomlIndex`]es, separated by commas.
+struct FmtInput {
+    format: LitStr,
+    indexes: Vec<TomlIndex>,
+}
+
+impl Parse for FmtInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let format = input.parse()?;
+        let mut indexes = Vec::new();
+        while input.parse::<Comma>().is_ok() && !input.is_empty() {
+            indexes.push(input.parse()?);
+        }
+        Ok(Self { format, indexes })
+    }
+}
+
+/// Format values from `Cargo.toml` into a string at compile time.
+///
+/// Each `{}` in the format string is replaced by the string form of the next value, which must be a scalar.
+/// Braces are escaped as `{{` and `}}`. The result is emitted as `&str` literal,
+/// and the number of placeholders must match the number of indexes.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_fmt;
+///
+/// const HELP: &str = include_toml_fmt!(
+///     "Version {} built with edition {}",
+///     "package"."version",
+///     "package"."edition"
+/// );
+/// assert_eq!(HELP, "Version 0.3.1 built with edition 2018");
+/// ```
+#[proc_macro]
+pub fn include_toml_fmt(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: FmtInput, document| {
+        let FmtInput { format, indexes } = input;
+        let values = indexes
+            .into_iter()
+            .map(|index| document.lookup(index).and_then(scalar_string))
+            .collect::<Result<Vec<_>, SynError>>()?;
+        let formatted =
+            fmt(&format.value(), &values).map_err(|e| SynError::new(format.span(), e))?;
+        Ok(formatted.to_token_stream())
+    })
+}
+
+/// Replaces the `{}` placeholders of `format` with `values`.
+fn fmt(format: &str, values: &[String]) -> Result<String, SynError> {
+    let mut formatted = String::with_capacity(format.len());
+    let arguments = values.len();
+    let mut values = values.iter();
+    let mut placeholders = 0;
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                formatted.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                placeholders += 1;
+                if let Some(value) = values.next() {
+                    formatted.push_str(value);
+                }
+            }
+            ('{', _) | ('}', _) => {
+                return Err(error(format!(
+                    "Invalid format string \"{}\", expected `{{}}` or escaped braces",
+                    format
+                )))
+            }
+            _ => formatted.push(c),
+        }
+    }
+    if placeholders != arguments {
+        return Err(error(format!(
+            "Format string has {} placeholders, but {} values are given",
+            placeholders, arguments
+        )));
+    }
+    Ok(formatted)
+}
+
+/// Emit tokens depending on a boolean in `Cargo.toml` at compile time.
+///
+/// Emits the tokens in braces if the value is `true` and nothing if it is `false`.
+/// Unlike [`include_toml_when!`], the value must exist and be a boolean.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_if;
+///
+/// include_toml_if!("lib"."proc-macro", {
+///     fn is_proc_macro() -> bool {
+///         true
+///     }
+/// });
+///
+/// assert!(is_proc_macro());
+/// ```
+///
+/// ```rust,compile_fail
+/// use include_cargo_toml2::include_toml_if;
+///
+/// // error: Expected boolean, found string
+/// include_toml_if!("package"."name", {
+///     fn name() {}
+/// });
+/// ```
+#[proc_macro]
+pub fn include_toml_if(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: WhenInput, document| {
+        if_true(document.lookup(input.index)?, input.body)
+    })
+}
+
+/// Returns `body` if `value` is `true`, and no tokens if it is `false`.
+fn if_true(value: Value, body: TokenStream2) -> Result<TokenStream2, SynError> {
+    match value {
+        Value::Boolean(true) => Ok(body),
+        Value::Boolean(false) => Ok(TokenStream2::new()),
+        other => Err(error(format!(
+            "Expected boolean, found {}",
+            other.type_str()
+        ))),
+    }
+}
+
+/// Parse statistics of a table or array in `Cargo.toml` at compile time.
+///
+/// Emits a `(direct, leaves, depth)` tuple of type `(usize, usize, usize)`, where `direct` is the number of keys
+/// or elements of the value, `leaves` the number of scalars as in [`include_toml_leaf_count!`],
+/// and `depth` the maximum nesting of tables and arrays, which is `1` for a table of scalars.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_stats;
+///
+/// assert_eq!(include_toml_stats!("lib"), (1, 1, 1));
+/// assert_eq!(include_toml_stats!("package"."keywords"), (5, 5, 1));
+/// ```
+#[proc_macro]
+pub fn include_toml_stats(input: TokenStream) -> TokenStream {
+    expand(input, to_stats)
+}
+
+/// Converts a table or array to the tuple of its statistics.
+fn to_stats(value: Value) -> Result<TokenStream2, SynError> {
+    let direct = match &value {
+        Value::Array(a) => a.len(),
+        Value::Table(t) => t.len(),
+        other => {
+            return Err(error(format!(
+                "Expected table or array, found {}",
+                other.type_str()
+            )))
+        }
+    };
+    let leaves = Literal::usize_suffixed(leaf_count(&value));
+    let depth = Literal::usize_suffixed(depth(&value));
+    let direct = Literal::usize_suffixed(direct);
+    Ok(quote!((#direct, #leaves, #depth)))
+}
+
+/// Returns the maximum nesting of tables and arrays in `value`, which is `0` for scalars.
+fn depth(value: &Value) -> usize {
+    match value {
+        Value::Array(a) => 1 + a.iter().map(depth).max().unwrap_or(0),
+        Value::Table(t) => 1 + t.values().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Parse a string from `Cargo.toml` at compile time obfuscated with ROT13.
+///
+/// Emits a `(obfuscated, decode)` tuple, where `obfuscated` is the string with ASCII letters rotated by 13
+/// as [`&str`], and `decode` is a `fn(&str) -> String` that restores the original.
+/// `decode` is not a `const fn` and can only be called at runtime, because it allocates a [`String`]
+/// and is returned as function pointer. This hides strings from casual inspection of the binary,
+/// but is no protection of secrets.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_rot13;
+///
+/// let (hidden, decode) = include_toml_rot13!("package"."name");
+/// assert_eq!(hidden, "vapyhqr-pnetb-gbzy2");
+/// assert_eq!(decode(hidden), "include-cargo-toml2");
+/// ```
+#[proc_macro]
+pub fn include_toml_rot13(input: TokenStream) -> TokenStream {
+    expand(input, to_rot13)
+}
+
+/// Converts a string to its ROT13 form together with a decoder.
+fn to_rot13(value: Value) -> Result<TokenStream2, SynError> {
+    let obfuscated = rot13(&string(value)?);
+    Ok(quote! {{
+        const fn rot13(byte: u8) -> u8 {
+            match byte {
+                b'a'..=b'm' | b'A'..=b'M' => byte + 13,
+                b'n'..=b'z' | b'N'..=b'Z' => byte - 13,
+                _ => byte,
+            }
+        }
+        fn decode(s: &str) -> ::std::string::String {
+            s.chars()
+                .map(|c| if c.is_ascii() { rot13(c as u8) as char } else { c })
+                .collect()
+        }
+        (#obfuscated, decode as fn(&str) -> ::std::string::String)
+    }})
+}
+
+/// Rotates the ASCII letters of `s` by 13.
+fn rot13(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            'a'..='m' | 'A'..='M' => (c as u8 + 13) as char,
+            'n'..='z' | 'N'..='Z' => (c as u8 - 13) as char,
+            _ => c,
+        })
+        .collect()
+}
+
+/// Parse statistics of an array of integers from `Cargo.toml` at compile time.
+///
+/// Emits a `(min, max, sum)` tuple of type `(i64, i64, i64)`.
+/// Fails if the array is empty, an element is not an integer or the sum overflows.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_array_stats;
+///
+/// // [package.metadata]
+/// // samples = [3, -1, 7]
+/// const STATS: (i64, i64, i64) = include_toml_array_stats!(first ["tests/fixtures/metadata.toml"], "package"."metadata"."samples");
+/// assert_eq!(STATS, (-1, 7, 9));
+/// ```
+#[proc_macro]
+pub fn include_toml_array_stats(input: TokenStream) -> TokenStream {
+    expand(input, to_array_stats)
+}
+
+/// Converts a non-empty array of integers to a `(min, max, sum)` tuple.
+fn to_array_stats(value: Value) -> Result<TokenStream2, SynError> {
+    let ints = array(value)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, element)| integer(element).map_err(|e| error(format!("Element {}: {}", i, e))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (min, max) = match (ints.iter().min(), ints.iter().max()) {
+        (Some(min), Some(max)) => (Literal::i64_suffixed(*min), Literal::i64_suffixed(*max)),
+        _ => return Err(error("Cannot compute statistics of empty array")),
+    };
+    let sum = ints
+        .iter()
+        .try_fold(0i64, |sum, int| sum.checked_add(*int))
+        .map(Literal::i64_suffixed)
+        .ok_or_else(|| error("Sum of array overflows i64"))?;
+    Ok(quote!((#min, #max, #sum)))
+}
+
+/// Struct that parses input of [`include_toml_default_impl`].
+///
+/// Input should consist of a type and fields `name: index` in braces, separated by commas,
+/// optionally followed by `..` and an expression for the remaining fields.
+struct DefaultImplInput {
+    ty: SynPath,
+    fields: Vec<(Ident, TomlIndex)>,
+    base: Option<Expr>,
+}
+
+impl Parse for DefaultImplInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let ty = input.parse()?;
+        let content;
+        braced!(content in input);
+        let mut fields = Vec::new();
+        let mut base = None;
+        while !content.is_empty() {
+            if content.parse::<Dot2>().is_ok() {
+                base = Some(content.parse()?);
+                break;
+            }
+            let field = content.parse()?;
+            content.parse::<Colon>()?;
+            fields.push((field, content.parse()?));
+            if content.parse::<Comma>().is_err() {
+                break;
+            }
+        }
+        Ok(Self { ty, fields, base })
+    }
+}
+
+/// Implement [`Default`] for a struct with values from `Cargo.toml` at compile time.
+///
+/// Emits an `impl Default` whose `default()` returns the struct with the given fields set to values looked up
+/// in `Cargo.toml`. The values are emitted like the `raw` modifier of [`include_toml!`], so numbers take the type
+/// of the field, and type mismatches are reported by the compiler as usual.
+/// Other fields can be taken from an expression after `..`, which must not call `Default::default()` of the
+/// same type, as that would recurse.
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_default_impl;
+///
+/// struct Package {
+///     name: &'static str,
+///     edition: &'static str,
+///     verbose: bool,
+/// }
+///
+/// impl Package {
+///     const QUIET: Self = Self { name: "", edition: "", verbose: false };
+/// }
+///
+/// include_toml_default_impl!(Package {
+///     name: "package"."name",
+///     edition: "package"."edition",
+///     ..Package::QUIET
+/// });
+///
+/// let package = Package::default();
+/// assert_eq!(package.name, "include-cargo-toml2");
+/// assert_eq!(package.edition, "2018");
+/// assert!(!package.verbose);
+/// ```
+#[proc_macro]
+pub fn include_toml_default_impl(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: DefaultImplInput, document| {
+        let options = Options {
+            raw: true,
+            ..Options::default()
+        };
+        let fields = input
+            .fields
+            .into_iter()
+            .map(|(field, index)| {
+                let value = translate_with(document.lookup(index)?, &options)?;
+                Ok(quote!(#field: #value))
+            })
+            .collect::<Result<Vec<_>, SynError>>()?;
+        Ok(default_impl(&input.ty, &fields, input.base.as_ref()))
+    })
+}
+
+/// Emits an `impl Default` for `ty` that initializes `fields` and takes the rest from `base`.
+fn default_impl(ty: &SynPath, fields: &[TokenStream2], base: Option<&Expr>) -> TokenStream2 {
+    let base = base.map(|base| quote!(..#base));
+    quote! {
+        impl ::core::default::Default for #ty {
+            fn default() -> Self {
+                Self {
+                    #(#fields,)*
+                    #base
+                }
+            }
+        }
+    }
+}
+
+/// Struct that parses input of [`include_toml_tokens`].
+///
+/// Input should consist of a [`TomlIndex`], optionally followed by a comma and the path of a macro.
+struct TokensInput {
+    index: TomlIndex,
+    callback: Option<SynPath>,
+}
+
+impl Parse for TokensInput {
+    fn parse(input: &ParseBuffer) -> Result<Self, SynError> {
+        let index = input.parse()?;
+        let callback = match input.parse::<Comma>() {
+            Ok(_) => Some(input.parse()?),
+            Err(_) => None,
+        };
+        Ok(Self { index, callback })
+    }
+}
+
+/// Parse a value from `Cargo.toml` at compile time as a single token tree.
+///
+/// The value is emitted like [`include_toml!`], but always as exactly one token tree,
+/// so it can be matched by `$value:tt` in `macro_rules!`:
+///
+/// - strings and datetimes are string literals, which also match `$value:literal`
+/// - non-negative integers and floats are suffixed literals like `5i64`, which also match `$value:literal`
+/// - booleans are the identifiers `true` and `false`, which also match `$value:literal`
+/// - negative integers and floats are wrapped in parentheses, like `(-5i64)`
+/// - arrays and tables are tuples, which are delimited by parentheses
+///
+/// As macros do not expand their arguments, the value is passed to the macro given after a comma,
+/// which is invoked as `callback!(value)`:
+///
+/// ```rust
+/// use include_cargo_toml2::include_toml_tokens;
+///
+/// macro_rules! shout {
+///     ($name:literal) => {
+///         concat!($name, "!")
+///     };
+/// }
+///
+/// assert_eq!(include_toml_tokens!("package"."name", shout), "include-cargo-toml2!");
+/// assert_eq!(include_toml_tokens!("package"."version"), "0.3.1");
+/// ```
+#[proc_macro]
+pub fn include_toml_tokens(input: TokenStream) -> TokenStream {
+    expand_with(input, |input: TokensInput, document| {
+        let tree = token_tree(document.lookup(input.index)?)?;
+        Ok(match input.callback {
+            Some(callback) => quote!(#callback!(#tree)),
+            None => tree,
+        })
+    })
+}
+
+/// Converts `value` like [`translate`], wrapping it in parentheses if it is not a single token tree.
+fn token_tree(value: Value) -> Result<TokenStream2, SynError> {
+    let tokens = translate(value)?;
+    if tokens.clone().into_iter().count() == 1 {
+        Ok(tokens)
+    } else {
+        Ok(quote!((#tokens)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "include_toml_regex")]
+    use crate::to_regex;
+    use crate::{
+        about, alias, array_mode, assert_env, assert_stable, author_email, base64, bytes_size,
+        call, check_range, clap, const_item, const_name, datetime_kind, default_impl, difference,
+        dispatch, documented_const, dotted, email, entry, expect, find, first, fixed_len, fmt,
+        fnv1a, follow, gh_repo, hex, if_true, into, is_empty, iter, join, leaf_count, lookup,
+        lookup_in, lookup_opt, member, merge, metadata, out_dir, parse, parse_fn, ratio, require,
+        rev, rot13, rust_type, scalar_string, spread_consts, stable, static_assert_type, strings,
+        target_item, to_array_stats, to_base64_bytes, to_bool, to_byte_array, to_cased, to_char,
+        to_cow, to_cstr, to_date, to_hashmap, to_i64_array, to_int_pairs, to_line, to_nonzero_u32,
+        to_option, to_osstr, to_percent, to_ratio, to_result, to_rot13, to_set, to_static,
+        to_stats, to_strs, to_structs, to_sum, to_time, to_type, token_tree, transform, translate,
+        translate_with, truthy, unique, unquote, variant, version_ge, when, wildcard, CfgInput,
+        ConstDefInput, Datetimes, DefaultImplInput, Document, FmtInput, HexInput, IncludeInput,
+        IntoInput, Options, ParseFnInput, RangeInput, RequireInput, Source, Sourced, Span2,
+        StructsInput, TokensInput, TomlIndex, VariantInput, WhenInput, MAX_DENOMINATOR,
+    };
+    #[cfg(feature = "include_toml_source")]
+    use crate::{source, DocumentMut};
+    use quote::{quote, ToTokens};
+    use std::env::var;
+    use std::path::Path;
+    use syn::{Expr, Ident, LitInt, LitStr, Type};
+    use toml::Value;
+
+    /// Wraps `toml` as document read from `Cargo.toml`.
+    fn document(toml: Value) -> Document {
+        Document {
+            value: toml,
+            file: "Cargo.toml".to_string(),
+        }
+    }
+
+    #[test]
+    fn should_parse_when_cargo_toml_is_valid() {
+        let dir = var("CARGO_MANIFEST_DIR").expect("Environment variable CARGO_MANIFEST_DIR must be set!");
+
+        let path = Path::new(&dir).join("Cargo.toml");
+        println!("{}", dir);
+        let toml = parse(&path).unwrap();
+
+        assert_eq!("include-cargo-toml2", toml["package"]["name"].as_str().unwrap());
+    }
+
+    #[test]
+    fn should_fetch_attribute_when_cargo_toml_is_given() {
+        let cargo_toml = r#"
+        [package]
+        edition="2021"
+        version="0.1.0"
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let index = syn::parse_str(r#""package"."version""#).unwrap();
+
+        let result = lookup(index, &toml).unwrap();
+
+        assert_eq!("0.1.0", result.as_str().unwrap());
+    }
+
+    #[test]
+    fn should_fetch_custom_attribute_when_cargo_toml_is_given() {
+        let cargo_toml = r#"
+        [package]
+        edition="2021"
+        [package.metadata.deb]
+        revision=4
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let index = syn::parse_str(r#""package"."metadata"."deb"."revision""#).unwrap();
+
+        let result = lookup(index, &toml).unwrap();
+
+        assert_eq!(4, result.as_integer().unwrap());
+    }
+
+    #[test]
+    fn should_fail_with_custom_message_when_key_is_missing() {
+        let cargo_toml = r#"
+        [package]
+        edition="2021"
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let index = syn::parse_str(r#""package"."version""#).unwrap();
+        let message = syn::parse_str(r#""version must be set""#).unwrap();
+
+        let result = expect(index, &message, &document(toml));
+        assert_eq!(
+            "version must be set\nnote: Cannot find key \"version\" in Cargo.toml",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_single_character_string_to_char() {
+        let result = to_char(Value::String(",".to_string())).unwrap();
+
+        assert_eq!("','", result.to_string());
+    }
+
+    #[test]
+    fn should_fail_char_conversion_when_string_has_multiple_characters() {
+        let result = to_char(Value::String("ab".to_string()));
+
+        assert_eq!(
+            "Expected a single character, found 2 characters",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_char_conversion_when_string_is_empty() {
+        let result = to_char(Value::String(String::new()));
+
+        assert_eq!(
+            "Expected a single character, found 0 characters",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_integer_array_to_byte_array() {
+        let toml: Value = toml::from_str("bytes = [0, 1, 255]").unwrap();
+
+        let result = to_byte_array(toml["bytes"].clone()).unwrap();
+
+        assert_eq!("[0u8 , 1u8 , 255u8]", result.to_string());
+    }
+
+    #[test]
+    fn should_fail_byte_array_conversion_when_element_is_out_of_range() {
+        let toml: Value = toml::from_str("bytes = [0, 256]").unwrap();
+
+        let result = to_byte_array(toml["bytes"].clone());
+
+        assert_eq!(
+            "Element 1 is out of range for u8: 256",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_parse_first_file_when_all_files_exist() {
+        let dir = var("CARGO_MANIFEST_DIR").unwrap();
+        let paths = [
+            "tests/fixtures/config.local.toml".to_string(),
+            "tests/fixtures/config.toml".to_string(),
+        ];
+
+        let Document { value: toml, file } = first(Path::new(&dir), &paths).unwrap();
+
+        assert_eq!(3000, toml["server"]["port"].as_integer().unwrap());
+        assert_eq!("config.local.toml", file);
+    }
+
+    #[test]
+    fn should_parse_fallback_file_when_first_file_is_missing() {
+        let dir = var("CARGO_MANIFEST_DIR").unwrap();
+        let paths = [
+            "tests/fixtures/missing.toml".to_string(),
+            "tests/fixtures/config.toml".to_string(),
+        ];
+
+        let Document { value: toml, file } = first(Path::new(&dir), &paths).unwrap();
+
+        assert_eq!(8080, toml["server"]["port"].as_integer().unwrap());
+        assert_eq!("config.toml", file);
+    }
+
+    #[test]
+    fn should_list_attempted_paths_when_no_file_exists() {
+        let dir = var("CARGO_MANIFEST_DIR").unwrap();
+        let paths = ["a.toml".to_string(), "b.toml".to_string()];
+
+        let result = first(Path::new(&dir), &paths);
+
+        assert_eq!(
+            r#"Cannot read any of ["a.toml", "b.toml"]"#,
+            result.err().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_unsuffixed_numbers_when_raw_is_set() {
+        let toml: Value = toml::from_str("count = 5\nscale = 0.5").unwrap();
+        let raw = Options {
+            raw: true,
+            ..Options::default()
+        };
+
+        assert_eq!(
+            "5i64",
+            translate(toml["count"].clone()).unwrap().to_string()
+        );
+        assert_eq!(
+            "5",
+            translate_with(toml["count"].clone(), &raw)
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            "0.5f64",
+            translate(toml["scale"].clone()).unwrap().to_string()
+        );
+        assert_eq!(
+            "0.5",
+            translate_with(toml["scale"].clone(), &raw)
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn should_pair_value_with_last_key_when_entry_exists() {
+        let cargo_toml = r#"
+        [dependencies]
+        serde = "1"
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let index = syn::parse_str(r#""dependencies"."serde""#).unwrap();
+
+        let result = entry(index, document(toml)).unwrap();
+
+        assert_eq!(r#"("serde" , "1")"#, result.to_string());
+    }
+
+    #[test]
+    fn should_fail_entry_when_key_is_missing() {
+        let toml: Value = toml::from_str("[dependencies]").expect("Cannot parse Cargo.toml");
+        let index = syn::parse_str(r#""dependencies"."serde""#).unwrap();
+
+        let result = entry(index, document(toml));
+
+        assert_eq!(
+            r#"Cannot find key "serde" in Cargo.toml"#,
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "include_toml_source")]
+    fn should_keep_comments_when_selecting_source() {
+        let cargo_toml = r#"
+[package]
+edition = "2021"
+
+[package.metadata.deb]
+# Bumped on every packaging change
+revision = 4
+"#;
+
+        let document: DocumentMut = cargo_toml.parse().expect("Cannot parse Cargo.toml");
+        let index = syn::parse_str(r#""package"."metadata""#).unwrap();
+
+        let result = source(index, &document, "Cargo.toml").unwrap();
+
+        assert_eq!(
+            "[deb]\n# Bumped on every packaging change\nrevision = 4\n",
+            result
+        );
+    }
+
+    #[test]
+    fn should_fail_translation_when_value_is_nested_too_deep() {
+        let toml: Value = toml::from_str("a = { b = { c = { d = 1 } } }").unwrap();
+        let shallow = Options {
+            max_depth: 2,
+            ..Options::default()
+        };
+
+        let result = translate_with(toml, &shallow);
+
+        assert_eq!(
+            "Value is nested deeper than 2 levels",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_float_to_exact_ratio() {
+        assert_eq!((1, 2), ratio(0.5, MAX_DENOMINATOR));
+        assert_eq!((1, 4), ratio(0.25, MAX_DENOMINATOR));
+        assert_eq!((-3, 2), ratio(-1.5, MAX_DENOMINATOR));
+        assert_eq!((2, 1), ratio(2.0, MAX_DENOMINATOR));
+    }
+
+    #[test]
+    fn should_approximate_float_when_denominator_is_capped() {
+        assert_eq!((22, 7), ratio(std::f64::consts::PI, 100));
+    }
+
+    #[test]
+    fn should_emit_ratio_tuple_when_value_is_float() {
+        let result = to_ratio(Value::Float(0.25)).unwrap();
+
+        assert_eq!("(1i64 , 4i64)", result.to_string());
+    }
+
+    #[test]
+    fn should_join_adjacent_string_literals_to_single_key() {
+        let cargo_toml = r#"
+        [package]
+        version="0.1.0"
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let index = syn::parse_str(r#""pack" "age"."version""#).unwrap();
+
+        let result = lookup(index, &toml).unwrap();
+
+        assert_eq!("0.1.0", result.as_str().unwrap());
+    }
+
+    #[test]
+    fn should_load_file_relative_to_out_dir_when_it_is_set() {
+        let dir = var("CARGO_MANIFEST_DIR").unwrap() + "/tests/fixtures";
+
+        let unset = out_dir(None, "config.toml");
+        let Document { value: toml, file } = out_dir(Some(dir), "config.toml").unwrap();
+
+        assert_eq!(
+            "Environment variable OUT_DIR not set, is there a build script?",
+            unset.err().unwrap().to_string()
+        );
+        assert_eq!(8080, toml["server"]["port"].as_integer().unwrap());
+        assert_eq!("config.toml", file);
+    }
+
+    #[test]
+    fn should_build_hashmap_when_table_is_homogeneous() {
+        let toml: Value = toml::from_str("a = 1\nb = 2").unwrap();
+
+        let result = to_hashmap(toml).unwrap();
+
+        assert_eq!(
+            r#":: std :: collections :: HashMap :: from ([("a" , 1i64) , ("b" , 2i64)])"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_hashmap_when_table_is_heterogeneous() {
+        let toml: Value = toml::from_str("a = 1\nb = \"2\"").unwrap();
+
+        let result = to_hashmap(toml);
+
+        assert_eq!(
+            r#"Expected integer for key "b", found string"#,
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fetch_negative_integer_when_cargo_toml_is_given() {
+        let cargo_toml = r#"
+        [package.metadata]
+        offset=-5
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let index = syn::parse_str(r#""package"."metadata"."offset""#).unwrap();
+
+        let result = lookup(index, &toml).unwrap();
+
+        let expected: syn::Expr = syn::parse_str("-5i64").unwrap();
+        let tokens = translate(result).unwrap();
+        assert!(syn::parse2::<syn::Expr>(tokens.clone()).is_ok());
+        assert_eq!(expected.to_token_stream().to_string(), tokens.to_string());
+    }
+
+    #[test]
+    fn should_accept_value_when_it_is_in_range() {
+        assert!(check_range(1, Some(1), Some(65535), true).is_ok());
+        assert!(check_range(65535, Some(1), Some(65535), true).is_ok());
+        assert!(check_range(-5, None, Some(0), false).is_ok());
+        assert!(check_range(i64::MAX, Some(0), None, false).is_ok());
+    }
+
+    #[test]
+    fn should_fail_when_value_is_out_of_range() {
+        let above = check_range(70000, Some(1), Some(65535), true);
+        let at_exclusive_end = check_range(5, Some(0), Some(5), false);
+        let below = check_range(-1, Some(0), None, false);
+
+        assert_eq!(
+            "Value 70000 is out of range 1..=65535",
+            above.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Value 5 is out of range 0..5",
+            at_exclusive_end.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Value -1 is out of range 0..",
+            below.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_parse_range_when_it_has_negative_bounds() {
+        let input: RangeInput = syn::parse_str(r#""config"."offset", -10..=-1"#).unwrap();
+
+        assert_eq!(
+            (Some(-10), Some(-1), true),
+            (input.start, input.end, input.inclusive)
+        );
+    }
+
+    #[test]
+    fn should_name_file_when_parsing_fails() {
+        let dir = var("CARGO_MANIFEST_DIR").unwrap();
+
+        let result = parse(&Path::new(&dir).join("tests/fixtures/invalid.toml"));
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("Cannot parse invalid.toml: "));
+    }
+
+    #[test]
+    fn should_name_file_when_reading_fails() {
+        let dir = var("CARGO_MANIFEST_DIR").unwrap();
+
+        let result = parse(&Path::new(&dir).join("tests/fixtures/missing.toml"));
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("Cannot read missing.toml: "));
+    }
+
+    #[test]
+    fn should_emit_none_when_value_is_empty_or_missing() {
+        let toml: Value = toml::from_str("proxy = \"\"\n[tls]").unwrap();
+
+        let empty_string = to_option(Some(toml["proxy"].clone())).unwrap();
+        let empty_table = to_option(Some(toml["tls"].clone())).unwrap();
+        let missing = to_option(None).unwrap();
+
+        assert_eq!(
+            ":: core :: option :: Option :: None",
+            empty_string.to_string()
+        );
+        assert_eq!(
+            ":: core :: option :: Option :: None",
+            empty_table.to_string()
+        );
+        assert_eq!(":: core :: option :: Option :: None", missing.to_string());
+    }
+
+    #[test]
+    fn should_emit_some_when_value_is_present() {
+        let result = to_option(Some(Value::String("http://proxy".to_string()))).unwrap();
+
+        assert_eq!(
+            r#":: core :: option :: Option :: Some ("http://proxy")"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_fetch_opt_level_when_it_is_integer_or_string() {
+        let cargo_toml = r#"
+        [profile.dev]
+        opt-level=1
+        [profile.release]
+        opt-level="z"
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let dev = syn::parse_str(r#""profile"."dev"."opt-level""#).unwrap();
+        let release = syn::parse_str(r#""profile"."release"."opt-level""#).unwrap();
+
+        let dev = translate(lookup(dev, &toml).unwrap()).unwrap();
+        let release = translate(lookup(release, &toml).unwrap()).unwrap();
+
+        assert_eq!("1i64", dev.to_string());
+        assert_eq!(r#""z""#, release.to_string());
+    }
+
+    #[test]
+    fn should_compute_known_fnv1a_hashes() {
+        assert_eq!(0xcbf29ce484222325, fnv1a(b""));
+        assert_eq!(0xaf63dc4c8601ec8c, fnv1a(b"a"));
+        assert_eq!(0x85944171f73967e8, fnv1a(b"foobar"));
+    }
+
+    #[test]
+    fn should_hash_string_form_of_scalars() {
+        let string = scalar_string(Value::String("5".to_string())).unwrap();
+        let integer = scalar_string(Value::Integer(5)).unwrap();
+
+        assert_eq!(fnv1a(string.as_bytes()), fnv1a(integer.as_bytes()));
+        assert!(scalar_string(Value::Array(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn should_coerce_truthy_and_falsy_values() {
+        for truthy_value in ["yes", "Yes", "ON", "true", "1"] {
+            assert!(truthy(Value::String(truthy_value.to_string())).unwrap());
+        }
+        for falsy_value in ["no", "NO", "off", "False", "0"] {
+            assert!(!truthy(Value::String(falsy_value.to_string())).unwrap());
+        }
+        assert!(truthy(Value::Integer(1)).unwrap());
+        assert!(!truthy(Value::Integer(0)).unwrap());
+        assert!(truthy(Value::Boolean(true)).unwrap());
+    }
+
+    #[test]
+    fn should_fail_coercion_when_value_is_unrecognized() {
+        let string = truthy(Value::String("maybe".to_string()));
+        let integer = truthy(Value::Integer(2));
+
+        assert_eq!(
+            r#"Cannot interpret "maybe" as boolean"#,
+            string.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Cannot interpret 2 as boolean",
+            integer.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_find_table_when_key_matches_once() {
+        let cargo_toml = r#"
+        [[bin]]
+        name="server"
+        path="src/server.rs"
+        [[bin]]
+        name="mytool"
+        path="src/mytool.rs"
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let expected = Value::String("mytool".to_string());
+
+        let table = find(toml["bin"].clone(), "name", &expected).unwrap();
+
+        assert_eq!("src/mytool.rs", table["path"].as_str().unwrap());
+    }
+
+    #[test]
+    fn should_fail_find_when_no_or_several_tables_match() {
+        let cargo_toml = r#"
+        [[bin]]
+        name="a"
+        [[bin]]
+        name="a"
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+
+        let missing = find(toml["bin"].clone(), "name", &Value::String("b".to_string()));
+        let ambiguous = find(toml["bin"].clone(), "name", &Value::String("a".to_string()));
+
+        assert_eq!(
+            r#"Cannot find table with "name" = "b" in array"#,
+            missing.unwrap_err().to_string()
+        );
+        assert_eq!(
+            r#"Found 2 tables with "name" = "a" in array, expected one"#,
+            ambiguous.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_value_with_from_when_type_is_given() {
+        let input: IntoInput = syn::parse_str(r#"Version, "package"."version""#).unwrap();
+
+        let result = into(
+            &input.ty,
+            translate(Value::String("0.1.0".to_string())).unwrap(),
+        );
+
+        assert_eq!(
+            r#"< Version as :: core :: convert :: From < _ >> :: from ("0.1.0")"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_merge_tables_recursively_when_override_is_given() {
+        let base: Value = toml::from_str(
+            "[server]\nport = 8080\nhosts = [\"a\", \"b\"]\n[log]\nlevel = \"info\"",
+        )
+        .unwrap();
+        let overlay: Value =
+            toml::from_str("[server]\nport = 9090\nhosts = [\"c\"]\ntls = true").unwrap();
+
+        let merged = merge(base, overlay);
+
+        assert_eq!(9090, merged["server"]["port"].as_integer().unwrap());
+        assert_eq!(1, merged["server"]["hosts"].as_array().unwrap().len());
+        assert!(merged["server"]["tls"].as_bool().unwrap());
+        assert_eq!("info", merged["log"]["level"].as_str().unwrap());
+    }
+
+    #[test]
+    fn should_check_emptiness_of_tables() {
+        let toml: Value =
+            toml::from_str("[dependencies]\nsyn = \"1\"\n[dev-dependencies]").unwrap();
+
+        assert!(!is_empty(Some(toml["dependencies"].clone())).unwrap());
+        assert!(is_empty(Some(toml["dev-dependencies"].clone())).unwrap());
+        assert!(is_empty(None).unwrap());
+    }
+
+    #[test]
+    fn should_fail_emptiness_check_when_value_is_scalar() {
+        let result = is_empty(Some(Value::Integer(0)));
+
+        assert_eq!(
+            "Expected table or array, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_sort_and_deduplicate_strings_when_building_set() {
+        let toml: Value = toml::from_str(r#"keywords = ["b", "c", "a", "b"]"#).unwrap();
+
+        let result = to_set(toml["keywords"].clone()).unwrap();
+
+        assert_eq!(r#"& ["a" , "b" , "c"]"#, result.to_string());
+    }
+
+    #[test]
+    fn should_fail_strings_when_array_has_other_types() {
+        let toml: Value = toml::from_str(r#"keywords = ["a", 1]"#).unwrap();
+
+        let result = strings(toml["keywords"].clone());
+
+        assert_eq!(
+            "Element 1: Expected string, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_translate_array_of_scalars_and_tables_to_valid_tuple() {
+        let toml: Value = toml::from_str(r#"mixed = [1, { a = 2, b = "c" }]"#).unwrap();
+
+        let tokens = translate(toml["mixed"].clone()).unwrap();
+
+        assert_eq!(
+            r#"(1i64 , (("a" , 2i64) , ("b" , "c") ,) ,)"#,
+            tokens.to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_literal_of_given_type_when_value_fits() {
+        let u16_type: Ident = syn::parse_str("u16").unwrap();
+        let f32_type: Ident = syn::parse_str("f32").unwrap();
+
+        assert_eq!(
+            "8080u16",
+            to_type(Value::Integer(8080), &u16_type)
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            "0.5f32",
+            to_type(Value::Float(0.5), &f32_type).unwrap().to_string()
+        );
+        assert_eq!(
+            "2f32",
+            to_type(Value::Integer(2), &f32_type).unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_type_conversion_when_value_does_not_fit() {
+        let u16_type: Ident = syn::parse_str("u16").unwrap();
+        let u8_type: Ident = syn::parse_str("u8").unwrap();
+        let string_type: Ident = syn::parse_str("String").unwrap();
+
+        let overflow = to_type(Value::Integer(70000), &u16_type);
+        let negative = to_type(Value::Integer(-1), &u8_type);
+        let float = to_type(Value::Float(0.5), &u8_type);
+        let unsupported = to_type(Value::Integer(1), &string_type);
+
+        assert_eq!(
+            "Value 70000 is out of range for u16",
+            overflow.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Value -1 is out of range for u8",
+            negative.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Cannot emit float 0.5 as u8",
+            float.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Unsupported type String, expected primitive numeric type",
+            unsupported.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_build_struct_per_table_when_fields_are_mapped() {
+        let cargo_toml = r#"
+        [[bin]]
+        name="server"
+        path="src/server.rs"
+        [[bin]]
+        name="mytool"
+        path="src/mytool.rs"
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let input: StructsInput =
+            syn::parse_str(r#"Entry, "bin", { name: "name", path: "path" }"#).unwrap();
+
+        let result =
+            to_structs(&input.ty, input.fields, toml["bin"].clone(), "Cargo.toml").unwrap();
+
+        assert_eq!(
+            r#"& [Entry { name : "server" , path : "src/server.rs" } , Entry { name : "mytool" , path : "src/mytool.rs" }]"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_name_element_when_struct_field_is_missing() {
+        let cargo_toml = r#"
+        [[bin]]
+        name="server"
+        path="src/server.rs"
+        [[bin]]
+        name="mytool"
+        "#;
+
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let input: StructsInput =
+            syn::parse_str(r#"Entry, "bin", { name: "name", path: "path" }"#).unwrap();
+
+        let result = to_structs(&input.ty, input.fields, toml["bin"].clone(), "Cargo.toml");
+
+        assert_eq!(
+            r#"Element 1: Cannot find key "path" in Cargo.toml"#,
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_ok_or_err_when_deferring_errors() {
+        let toml: Value = toml::from_str("[package]\nversion = \"0.1.0\"").unwrap();
+        let version = syn::parse_str(r#""package"."version""#).unwrap();
+        let homepage = syn::parse_str(r#""package"."homepage""#).unwrap();
+
+        let ok = to_result(lookup(version, &toml).and_then(translate));
+        let err = to_result(lookup(homepage, &toml).and_then(translate));
+
+        assert_eq!(
+            r#":: core :: result :: Result :: < _ , & 'static str > :: Ok ("0.1.0")"#,
+            ok.to_string()
+        );
+        assert_eq!(
+            r#":: core :: result :: Result :: < _ , & 'static str > :: Err ("Cannot find key \"homepage\" in Cargo.toml")"#,
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn should_find_workspace_member_by_package_name() {
+        let dir = var("CARGO_MANIFEST_DIR").unwrap();
+        let member_dir = Path::new(&dir).join("tests/fixtures/workspace/crates/core");
+
+        let macros = member(&member_dir, "workspace-macros").unwrap();
+        let cli = member(&member_dir, "workspace-cli").unwrap();
+
+        assert_eq!("1.1.0", macros["package"]["version"].as_str().unwrap());
+        assert_eq!("2.0.0", cli["package"]["version"].as_str().unwrap());
+    }
+
+    #[test]
+    fn should_fail_when_workspace_member_does_not_exist() {
+        let dir = var("CARGO_MANIFEST_DIR").unwrap();
+        let member_dir = Path::new(&dir).join("tests/fixtures/workspace/crates/core");
+
+        let result = member(&member_dir, "other-crate");
+
+        assert_eq!(
+            r#"Cannot find workspace member "other-crate""#,
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_match_wildcards_in_member_patterns() {
+        assert!(wildcard("*", "core"));
+        assert!(wildcard("crate-*", "crate-core"));
+        assert!(wildcard("*-core", "crate-core"));
+        assert!(!wildcard("crate-*", "core"));
+        assert!(!wildcard("core", "cores"));
+    }
+
+    #[test]
+    fn should_describe_error_when_indexing_fails() {
+        let toml: Value = toml::from_str("[package]\nname = \"a\"\nauthors = [\"b\"]").unwrap();
+        let into_string = syn::parse_str(r#""package"."name".0"#).unwrap();
+        let out_of_bounds = syn::parse_str(r#""package"."authors".1"#).unwrap();
+
+        let into_string = lookup(into_string, &toml);
+        let out_of_bounds = lookup(out_of_bounds, &toml);
+
+        assert_eq!(
+            "Cannot index string with 0",
+            into_string.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Index 1 is out of bounds",
+            out_of_bounds.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_derive_rust_type_of_translated_value() {
+        let toml: Value = toml::from_str(r#"value = [1, "a", { b = true, c = 0.5 }]"#).unwrap();
+
+        let result = rust_type(&toml["value"]);
+
+        assert_eq!(
+            "(i64 , & 'static str , ((& 'static str , bool) , (& 'static str , f64) ,) ,)",
+            result.to_string()
+        );
+    }
+
+    const DATETIMES: &str = r#"
+        offset = 1979-05-27T07:32:00Z
+        local = 1979-05-27T07:32:00
+        date = 1979-05-27
+        time = 07:32:00.5
+    "#;
+
+    #[test]
+    fn should_name_each_datetime_kind() {
+        let toml: Value = toml::from_str(DATETIMES).unwrap();
+
+        let result: Vec<&str> = ["offset", "local", "date", "time"]
+            .iter()
+            .map(|key| datetime_kind(toml[key].as_datetime().unwrap()))
+            .collect();
+
+        assert_eq!(
+            vec![
+                "offset datetime",
+                "local datetime",
+                "local date",
+                "local time"
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn should_convert_local_date_to_tuple() {
+        let toml: Value = toml::from_str(DATETIMES).unwrap();
+
+        let result = to_date(toml["date"].clone()).unwrap();
+
+        assert_eq!("(1979u16 , 5u8 , 27u8)", result.to_string());
+    }
+
+    #[test]
+    fn should_fail_date_conversion_when_datetime_has_time() {
+        let toml: Value = toml::from_str(DATETIMES).unwrap();
+
+        let offset = to_date(toml["offset"].clone());
+        let local = to_date(toml["local"].clone());
+        let time = to_date(toml["time"].clone());
+
+        assert_eq!(
+            "Expected local date, found offset datetime",
+            offset.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Expected local date, found local datetime",
+            local.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Expected local date, found local time",
+            time.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_local_time_to_tuple() {
+        let toml: Value = toml::from_str(DATETIMES).unwrap();
+
+        let result = to_time(toml["time"].clone()).unwrap();
+
+        assert_eq!("(7u8 , 32u8 , 0u8 , 500000000u32)", result.to_string());
+    }
+
+    #[test]
+    fn should_fail_time_conversion_when_datetime_has_date() {
+        let toml: Value = toml::from_str(DATETIMES).unwrap();
+
+        let result = to_time(toml["date"].clone());
+
+        assert_eq!(
+            "Expected local time, found local date",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_datetime_conversion_when_value_is_string() {
+        let result = to_date(Value::String("1979-05-27".to_string()));
+
+        assert_eq!(
+            "Expected datetime, found string",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_pass_array_elements_as_arguments() {
+        let toml: Value = toml::from_str(r#"args = ["a", 1, true]"#).unwrap();
+        let func: syn::Path = syn::parse_str("my::func").unwrap();
+
+        let result = call(&func, toml["args"].clone()).unwrap();
+
+        assert_eq!(r#"my :: func ("a" , 1i64 , true)"#, result.to_string());
+    }
+
+    #[test]
+    fn should_fail_call_when_value_is_not_array() {
+        let func: syn::Path = syn::parse_str("func").unwrap();
+
+        let result = call(&func, Value::Integer(1));
+
+        assert_eq!(
+            "Expected array, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_accept_array_with_unique_elements() {
+        let toml: Value = toml::from_str(r#"value = [1, 1.0, "1", [1]]"#).unwrap();
+
+        let result = unique(toml["value"].clone()).unwrap();
+
+        assert_eq!(toml["value"], result);
+    }
+
+    #[test]
+    fn should_fail_unique_when_array_has_duplicates() {
+        let toml: Value = toml::from_str(r#"value = ["a", "b", { c = 1 }, { c = 1 }]"#).unwrap();
+
+        let result = unique(toml["value"].clone());
+
+        assert_eq!(
+            "Element 3 is a duplicate of element 2",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_keep_strings_missing_in_second_array() {
+        let a: Value = toml::from_str(r#"value = ["a", "b", "c", "d"]"#).unwrap();
+        let b: Value = toml::from_str(r#"value = ["d", "b", "e"]"#).unwrap();
+
+        let result = difference(a["value"].clone(), b["value"].clone()).unwrap();
+
+        assert_eq!(r#"& ["a" , "c"]"#, result.to_string());
+    }
+
+    #[test]
+    fn should_fail_difference_when_element_is_not_string() {
+        let a: Value = toml::from_str(r#"value = ["a"]"#).unwrap();
+        let b: Value = toml::from_str("value = [1]").unwrap();
+
+        let result = difference(a["value"].clone(), b["value"].clone());
+
+        assert_eq!(
+            "Element 0: Expected string, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_translate_homogeneous_arrays_to_rust_arrays_when_arrays_are_enabled() {
+        let toml: Value = toml::from_str(r#"value = [[1, 2], [3, 4], [5, "a"], []]"#).unwrap();
+        let options = Options {
+            arrays: true,
+            ..Options::default()
+        };
+
+        let result = translate_with(toml["value"].clone(), &options).unwrap();
+
+        assert_eq!(
+            r#"([1i64 , 2i64 ,] , [3i64 , 4i64 ,] , (5i64 , "a" ,) , () ,)"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_read_array_mode_from_package_metadata() {
+        let enabled: Value =
+            toml::from_str("[package.metadata.include-cargo-toml2]\narray_mode = true").unwrap();
+        let disabled: Value =
+            toml::from_str("[package.metadata.include-cargo-toml2]\narray_mode = false").unwrap();
+        let missing: Value = toml::from_str("[package]\nname = \"a\"").unwrap();
+
+        assert!(array_mode(&enabled).unwrap());
+        assert!(!array_mode(&disabled).unwrap());
+        assert!(!array_mode(&missing).unwrap());
+    }
+
+    #[test]
+    fn should_fail_array_mode_when_setting_is_not_boolean() {
+        let toml: Value =
+            toml::from_str("[package.metadata.include-cargo-toml2]\narray_mode = 1").unwrap();
+
+        let result = array_mode(&toml);
+
+        assert_eq!(
+            "Expected boolean for array_mode, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_let_tuple_modifier_override_array_mode() {
+        let input: IncludeInput = syn::parse_str(r#"raw tuple "a""#).unwrap();
+
+        assert_eq!(Some(false), input.arrays);
+        assert!(input.options.raw);
+    }
+
+    #[test]
+    fn should_convert_about_to_tuple() {
+        let toml: Value =
+            toml::from_str("[package]\nname = \"a\"\nversion = \"1.0.0\"\ndescription = \"b\"")
+                .unwrap();
+
+        let result = about(&toml).unwrap();
+
+        assert_eq!(r#"("a" , "1.0.0" , "b")"#, result.to_string());
+    }
+
+    #[test]
+    fn should_default_about_description_to_empty_string() {
+        let toml: Value = toml::from_str("[package]\nname = \"a\"\nversion = \"1.0.0\"").unwrap();
+
+        let result = about(&toml).unwrap();
+
+        assert_eq!(r#"("a" , "1.0.0" , "")"#, result.to_string());
+    }
+
+    #[test]
+    fn should_fail_about_when_version_is_missing() {
+        let toml: Value = toml::from_str("[package]\nname = \"a\"").unwrap();
+
+        let result = about(&toml);
+
+        assert_eq!(
+            "Cannot find key \"version\" in Cargo.toml",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_clap_information_to_tuple() {
+        let toml: Value = toml::from_str(
+            "[package]\nname = \"a\"\nversion = \"1.0.0\"\nauthors = [\"b <b@c.d>\", \"e\"]",
+        )
+        .unwrap();
+
+        let result = clap(&toml).unwrap();
+
+        assert_eq!(
+            r#"("a" , "1.0.0" , "" , "b <b@c.d>, e")"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_body_when_value_is_truthy() {
+        let body = quote!(
+            fn x() {}
+        );
+
+        let result = when(Some(Value::String("yes".to_string())), body).unwrap();
+
+        assert_eq!("fn x () { }", result.to_string());
+    }
+
+    #[test]
+    fn should_emit_nothing_when_value_is_falsy_or_missing() {
+        let falsy = when(
+            Some(Value::Boolean(false)),
+            quote!(
+                fn x() {}
+            ),
+        )
+        .unwrap();
+        let missing = when(
+            None,
+            quote!(
+                fn x() {}
+            ),
+        )
+        .unwrap();
+
+        assert!(falsy.is_empty());
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn should_fail_when_value_is_not_boolean() {
+        let result = when(
+            Some(Value::Integer(2)),
+            quote!(
+                fn x() {}
+            ),
+        );
+
+        assert_eq!(
+            "Cannot interpret 2 as boolean",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_integer_array_to_i64_array() {
+        let toml: Value = toml::from_str("values = [1, -2, 3]").unwrap();
+
+        let result = to_i64_array(toml["values"].clone()).unwrap();
+
+        assert_eq!("[1i64 , - 2i64 , 3i64]", result.to_string());
+    }
+
+    #[test]
+    fn should_fail_i64_array_conversion_when_element_is_not_integer() {
+        let toml: Value = toml::from_str(r#"values = [1, 2.5, "3"]"#).unwrap();
+
+        let result = to_i64_array(toml["values"].clone());
+
+        assert_eq!(
+            "Element 1: Expected integer, found float",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_string_array_to_slice() {
+        let toml: Value = toml::from_str(r#"include = ["src/**/*.rs", "Cargo.toml"]"#).unwrap();
+
+        let result = to_strs(toml["include"].clone()).unwrap();
+
+        assert_eq!(r#"& ["src/**/*.rs" , "Cargo.toml"]"#, result.to_string());
+    }
+
+    #[test]
+    fn should_convert_empty_array_to_empty_slice() {
+        let toml: Value = toml::from_str("exclude = []").unwrap();
+
+        let result = to_strs(toml["exclude"].clone()).unwrap();
+
+        assert_eq!("& []", result.to_string());
+    }
+
+    #[test]
+    fn should_emit_type_assertion_alongside_value() {
+        let ty: Type = syn::parse_str("&str").unwrap();
+
+        let result = static_assert_type(&ty, quote!("0.3.1"));
+
+        assert_eq!(
+            r#"{ const _ : fn () = || { let _ : & str = "0.3.1" ; } ; "0.3.1" }"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_look_up_metadata_like_full_index() {
+        let toml: Value = toml::from_str("[package.metadata.deb]\nrevision = \"1\"").unwrap();
+        let full: TomlIndex = syn::parse_str(r#""package"."metadata"."deb"."revision""#).unwrap();
+        let short: TomlIndex = syn::parse_str(r#""deb"."revision""#).unwrap();
+
+        let expected = lookup(full, &toml).unwrap();
+        let result = lookup(metadata(short), &toml).unwrap();
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn should_convert_positive_integer_to_nonzero_u32() {
+        let result = to_nonzero_u32(Value::Integer(4)).unwrap();
+
+        assert!(result
+            .to_string()
+            .starts_with("match :: core :: num :: NonZeroU32 :: new (4u32)"));
+    }
+
+    #[test]
+    fn should_fail_nonzero_u32_conversion_when_integer_is_zero_or_out_of_range() {
+        let zero = to_nonzero_u32(Value::Integer(0));
+        let large = to_nonzero_u32(Value::Integer(1 << 32));
+
+        assert_eq!(
+            "Expected integer in 1..=4294967295, found 0",
+            zero.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Expected integer in 1..=4294967295, found 4294967296",
+            large.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_homogeneous_array_to_iterator() {
+        let toml: Value = toml::from_str("items = [1, 2, 3]").unwrap();
+
+        let result = iter(toml["items"].clone()).unwrap();
+
+        assert_eq!(
+            ":: core :: iter :: IntoIterator :: into_iter ([1i64 , 2i64 , 3i64])",
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_iterator_conversion_when_array_is_heterogeneous_or_empty() {
+        let toml: Value = toml::from_str(
+            r#"mixed = [1, "2"]
+empty = []"#,
+        )
+        .unwrap();
+
+        let mixed = iter(toml["mixed"].clone());
+        let empty = iter(toml["empty"].clone());
+
+        assert_eq!(
+            "Expected array with elements of the same type",
+            mixed.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Cannot infer element type of empty array",
+            empty.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_const_item_with_inferred_type() {
+        let name: Ident = syn::parse_str("PORT").unwrap();
+
+        let result = const_item(&name, Value::Integer(8080)).unwrap();
+
+        assert_eq!("const PORT : i64 = 8080i64 ;", result.to_string());
+    }
+
+    #[test]
+    fn should_emit_const_item_behind_target_os_cfg() {
+        let input: CfgInput =
+            syn::parse_str(r#""windows", WIN_PATH, "config"."win-path""#).unwrap();
+
+        let result = target_item(
+            &input.target_os,
+            &input.name,
+            Value::String("C:".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            r#"# [cfg (target_os = "windows")] const WIN_PATH : & 'static str = "C:" ;"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_string_to_osstr() {
+        let result = to_osstr(Value::String("README.md".to_string())).unwrap();
+
+        assert_eq!(
+            r#":: std :: ffi :: OsStr :: new ("README.md")"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_osstr_conversion_when_string_is_empty() {
+        let result = to_osstr(Value::String(String::new()));
+
+        assert_eq!("Expected non-empty string", result.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn should_add_help_to_error_of_segment() {
+        let toml: Value = toml::from_str("[package]\nname = \"a\"").unwrap();
+        let index: TomlIndex =
+            syn::parse_str(r#""package"."metadata"["run `myapp init`"]."key""#).unwrap();
+
+        let result = lookup(index, &toml);
+
+        assert_eq!(
+            "Cannot find key \"metadata\" in Cargo.toml\nhelp: run `myapp init`",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_ignore_help_when_segment_exists() {
+        let toml: Value = toml::from_str("[package]\nname = \"a\"").unwrap();
+        let index: TomlIndex = syn::parse_str(r#""package"["unused"]."name""#).unwrap();
+
+        let result = lookup(index, &toml).unwrap();
+
+        assert_eq!("a", result.as_str().unwrap());
+    }
+
+    #[test]
+    fn should_count_leaves_of_nested_subtree() {
+        let toml: Value = toml::from_str(
+            r#"
+            [config]
+            name = "a"
+            ports = [1, 2, [3]]
+            empty = []
+            [config.nested]
+            enabled = true
+            servers = [{ host = "b", port = 4 }]
+            "#,
+        )
+        .unwrap();
+
+        let result = leaf_count(&toml["config"]);
+
+        assert_eq!(7, result);
+    }
+
+    #[test]
+    fn should_join_index_items_by_dots() {
+        let index: TomlIndex = syn::parse_str(r#""package"."keywords"["help"].0"#).unwrap();
+
+        let result = dotted(&index);
+
+        assert_eq!("package.keywords.0", result);
+    }
+
+    #[test]
+    fn should_parse_sizes_with_units() {
+        let sizes = [
+            "512", "64 B", "2KB", "3MB", "1GB", "4KiB", "256MiB", "2 GiB",
+        ];
+
+        let result: Vec<u64> = sizes.iter().map(|s| bytes_size(s).unwrap()).collect();
+
+        assert_eq!(
+            vec![
+                512,
+                64,
+                2_000,
+                3_000_000,
+                1_000_000_000,
+                4096,
+                268_435_456,
+                2_147_483_648
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn should_fail_size_parsing_when_string_is_invalid() {
+        let unit = bytes_size("256 MiBs");
+        let number = bytes_size("MiB");
+        let overflow = bytes_size("18446744073709551615KB");
+
+        assert_eq!(
+            "Cannot parse \"256 MiBs\" as size in bytes",
+            unit.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Cannot parse \"MiB\" as size in bytes",
+            number.unwrap_err().to_string()
+        );
+        assert!(overflow.is_err());
+    }
+
+    #[test]
+    fn should_convert_string_to_nul_terminated_cstr() {
+        let result = to_cstr(Value::String("name".to_string())).unwrap();
+
+        assert!(result
+            .to_string()
+            .starts_with(r#"match :: core :: ffi :: CStr :: from_bytes_with_nul (b"name\0")"#));
+    }
+
+    #[test]
+    fn should_fail_cstr_conversion_when_string_contains_nul() {
+        let result = to_cstr(Value::String("na\0me".to_string()));
+
+        assert_eq!(
+            "String contains NUL byte at 2",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_string_to_uppercase_and_lowercase() {
+        let value = Value::String("Cargo-toml".to_string());
+
+        let upper = to_cased(value.clone(), str::to_uppercase).unwrap();
+        let lower = to_cased(value, str::to_lowercase).unwrap();
+
+        assert_eq!(r#""CARGO-TOML""#, upper.to_string());
+        assert_eq!(r#""cargo-toml""#, lower.to_string());
+    }
+
+    #[test]
+    fn should_fail_case_conversion_when_value_is_not_string() {
+        let result = to_cased(Value::Boolean(true), str::to_uppercase);
+
+        assert_eq!(
+            "Expected string, found boolean",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_unit_when_required_keys_are_present() {
+        let toml: Value = toml::from_str("[package]\nname = \"a\"\nversion = \"1.0.0\"").unwrap();
+        let input: RequireInput =
+            syn::parse_str(r#"["package"."name"], ["package"."version"]"#).unwrap();
+
+        let result = require(input.0, &toml).unwrap();
+
+        assert_eq!("()", result.to_string());
+    }
+
+    #[test]
+    fn should_list_all_missing_required_keys() {
+        let toml: Value =
+            toml::from_str("[package]\nname = \"\"\nkeywords = []\nversion = \"1.0.0\"").unwrap();
+        let input: RequireInput = syn::parse_str(
+            r#"["package"."name"], ["package"."license"], ["package"."version"], ["package"."keywords"]"#,
+        )
+        .unwrap();
+
+        let result = require(input.0, &toml);
+
+        assert_eq!(
+            "Missing required keys in Cargo.toml:\n- package.name: empty\n- package.license: missing\n- package.keywords: not a scalar",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_append_newline_to_string() {
+        let result = to_line(Value::String("a b".to_string())).unwrap();
+
+        assert_eq!(r#""a b\n""#, result.to_string());
+    }
+
+    #[test]
+    fn should_fail_line_conversion_when_value_is_not_string() {
+        let result = to_line(Value::Integer(1));
+
+        assert_eq!(
+            "Expected string, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    const ALIASES: &str = r#"
+        [package]
+        keywords = ["a", "b"]
+        [package.metadata.aliases]
+        second = "package.keywords.1"
+        dangling = "package.metadata.deb"
+    "#;
+
+    #[test]
+    fn should_look_up_value_through_alias() {
+        let toml: Value = toml::from_str(ALIASES).unwrap();
+        let table: TomlIndex = syn::parse_str(r#""package"."metadata"."aliases""#).unwrap();
+        let name: LitStr = syn::parse_str(r#""second""#).unwrap();
+
+        let result = alias(table, &name, &document(toml)).unwrap();
+
+        assert_eq!("b", result.as_str().unwrap());
+    }
+
+    #[test]
+    fn should_fail_alias_when_path_is_dangling() {
+        let toml: Value = toml::from_str(ALIASES).unwrap();
+        let table: TomlIndex = syn::parse_str(r#""package"."metadata"."aliases""#).unwrap();
+        let name: LitStr = syn::parse_str(r#""dangling""#).unwrap();
+
+        let result = alias(table, &name, &document(toml));
+
+        assert_eq!(
+            "Alias \"dangling\" refers to \"package.metadata.deb\": Cannot find key \"deb\" in Cargo.toml",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_alias_when_alias_is_missing() {
+        let toml: Value = toml::from_str(ALIASES).unwrap();
+        let table: TomlIndex = syn::parse_str(r#""package"."metadata"."aliases""#).unwrap();
+        let name: LitStr = syn::parse_str(r#""short""#).unwrap();
+
+        let result = alias(table, &name, &document(toml));
+
+        assert_eq!(
+            "Cannot find alias \"short\"",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "include_toml_regex")]
+    fn should_accept_valid_regular_expression() {
+        let result = to_regex(Value::String(r"^\d+\.\d+$".to_string())).unwrap();
+
+        assert_eq!(r#""^\\d+\\.\\d+$""#, result.to_string());
+    }
+
+    #[test]
+    #[cfg(feature = "include_toml_regex")]
+    fn should_fail_regex_conversion_when_pattern_is_invalid() {
+        let result = to_regex(Value::String("(a".to_string()));
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid regular expression: regex parse error:"));
+    }
+
+    #[test]
+    fn should_convert_boolean_to_bool_literal() {
+        let result = to_bool(Value::Boolean(true)).unwrap();
+
+        assert_eq!("true", result.to_string());
+    }
+
+    #[test]
+    fn should_fail_bool_conversion_when_value_is_not_boolean() {
+        let result = to_bool(Value::String("true".to_string()));
+
+        assert_eq!(
+            "Expected boolean, found string",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_join_strings_by_delimiter() {
+        let toml: Value = toml::from_str(r#"value = ["a", "b", "c"]"#).unwrap();
+
+        let comma = join(toml["value"].clone(), ", ").unwrap();
+        let none = join(toml["value"].clone(), "").unwrap();
+
+        assert_eq!(r#""a, b, c""#, comma.to_string());
+        assert_eq!(r#""abc""#, none.to_string());
+    }
+
+    #[test]
+    fn should_join_empty_array_to_empty_string() {
+        let result = join(Value::Array(Vec::new()), ", ").unwrap();
+
+        assert_eq!(r#""""#, result.to_string());
+    }
+
+    #[test]
+    fn should_fail_join_when_element_is_not_string() {
+        let toml: Value = toml::from_str(r#"value = ["a", 1]"#).unwrap();
+
+        let result = join(toml["value"].clone(), ", ");
+
+        assert_eq!(
+            "Element 1: Expected string, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    const POINTERS: &str = r#"
+        "a/b" = 1
+        "m~n" = 2
+        "10" = { list = ["x", "y"] }
+    "#;
+
+    #[test]
+    fn should_follow_json_pointer_with_escapes_and_indexes() {
+        let toml = document(toml::from_str(POINTERS).unwrap());
+        let follow_str = |pointer: &str| {
+            let pointer = LitStr::new(pointer, Span2::call_site());
+            follow(&pointer, &toml).unwrap()
+        };
+
+        assert_eq!(Value::Integer(1), follow_str("/a~1b"));
+        assert_eq!(Value::Integer(2), follow_str("/m~0n"));
+        assert_eq!(Value::String("y".to_string()), follow_str("/10/list/1"));
+        assert_eq!(toml.value, follow_str(""));
+    }
+
+    #[test]
+    fn should_fail_json_pointer_when_invalid_or_missing() {
+        let toml = document(toml::from_str(POINTERS).unwrap());
+        let follow_err = |pointer: &str| {
+            let pointer = LitStr::new(pointer, Span2::call_site());
+            follow(&pointer, &toml).unwrap_err().to_string()
+        };
+
+        assert_eq!("JSON Pointer must start with `/`", follow_err("a~1b"));
+        assert_eq!(
+            "Invalid escape in JSON Pointer item \"m~2n\"",
+            follow_err("/m~2n")
+        );
+        assert_eq!("Index 2 is out of bounds", follow_err("/10/list/2"));
+        assert_eq!("Cannot find key \"c\" in Cargo.toml", follow_err("/c"));
+    }
+
+    const COMMANDS: &str = r#"
+        [[commands]]
+        name = "build"
+        handler = "commands::build"
+
+        [[commands]]
+        name = "test"
+        handler = "run_tests"
+    "#;
+
+    #[test]
+    fn should_emit_match_arm_for_each_table() {
+        let toml: Value = toml::from_str(COMMANDS).unwrap();
+        let expr: Expr = syn::parse_str("name").unwrap();
+
+        let result = dispatch(&expr, toml["commands"].clone(), "name", "handler").unwrap();
+
+        assert_eq!(
+            "match name { \"build\" => :: core :: option :: Option :: Some (commands :: build) , \
+             \"test\" => :: core :: option :: Option :: Some (run_tests) , \
+             _ => :: core :: option :: Option :: None , }",
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_dispatch_when_names_are_duplicated() {
+        let toml: Value = toml::from_str(&COMMANDS.replace("\"test\"", "\"build\"")).unwrap();
+        let expr: Expr = syn::parse_str("name").unwrap();
+
+        let result = dispatch(&expr, toml["commands"].clone(), "name", "handler");
+
+        assert_eq!(
+            "Duplicate name \"build\" in element 1",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_translate_datetime_in_each_representation() {
+        let toml: Value = toml::from_str("built = 1979-05-27T07:32:00+01:00").unwrap();
+        let translate_dt = |input: &str| {
+            let input: IncludeInput = syn::parse_str(input).unwrap();
+            translate_with(toml["built"].clone(), &input.options)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(
+            r#""1979-05-27T07:32:00+01:00""#,
+            translate_dt(r#"dt=string "built""#)
+        );
+        assert_eq!(
+            "((1979u16 , 5u8 , 27u8) , (7u8 , 32u8 , 0u8 , 0u32) , 60i16)",
+            translate_dt(r#"dt=tuple "built""#)
+        );
+        assert_eq!("296634720i64", translate_dt(r#"dt=timestamp "built""#));
+    }
+
+    #[test]
+    fn should_fail_timestamp_when_datetime_has_no_offset() {
+        let toml: Value = toml::from_str("built = 1979-05-27T07:32:00").unwrap();
+        let options = Options {
+            datetimes: Datetimes::Timestamp,
+            ..Options::default()
+        };
+
+        let result = translate_with(toml["built"].clone(), &options);
+
+        assert_eq!(
+            "Expected offset datetime for timestamp, found local datetime",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_parsing_unknown_datetime_representation() {
+        let result = syn::parse_str::<IncludeInput>(r#"dt=unix "built""#);
+
+        assert_eq!(
+            "Unknown datetime representation `unix`",
+            result.err().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_string_to_enum_variant_ignoring_case() {
+        let input: VariantInput = syn::parse_str(r#"Mode { Debug, Release }, "mode""#).unwrap();
+
+        let result = variant(
+            &input.ty,
+            &input.variants,
+            Value::String("release".to_string()),
+        );
+
+        assert_eq!("Mode :: Release", result.unwrap().to_string());
+    }
+
+    #[test]
+    fn should_fail_variant_conversion_when_string_is_unknown() {
+        let input: VariantInput = syn::parse_str(r#"Mode { Debug, Release }, "mode""#).unwrap();
+
+        let result = variant(
+            &input.ty,
+            &input.variants,
+            Value::String("fast".to_string()),
+        );
+
+        assert_eq!(
+            "Unknown variant \"fast\", expected one of: Debug, Release",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_remove_one_layer_of_quotes() {
+        assert_eq!("value", unquote(r#""value""#));
+        assert_eq!(r#""value""#, unquote(r#"""value"""#));
+        assert_eq!("", unquote(r#""""#));
+    }
+
+    #[test]
+    fn should_keep_strings_without_surrounding_quotes() {
+        assert_eq!("value", unquote("value"));
+        assert_eq!(r#""value"#, unquote(r#""value"#));
+        assert_eq!(r#"""#, unquote(r#"""#));
+    }
+
+    #[test]
+    fn should_accept_string_of_fixed_length() {
+        let result = fixed_len(Value::String("EUR".to_string()), 3).unwrap();
+
+        assert_eq!(r#""EUR""#, result.to_string());
+    }
+
+    #[test]
+    fn should_fail_fixed_length_when_length_differs() {
+        let short = fixed_len(Value::String("EU".to_string()), 3);
+        let multibyte = fixed_len(Value::String("€UR".to_string()), 3);
+
+        assert_eq!(
+            "Expected string of 3 bytes, found 2 bytes",
+            short.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Expected string of 3 bytes, found 5 bytes",
+            multibyte.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_split_github_url_into_owner_and_repo() {
+        let urls = [
+            "https://github.com/owner/repo",
+            "https://github.com/owner/repo.git",
+            "https://github.com/owner/repo/",
+            "http://github.com/owner/repo",
+        ];
+
+        for url in &urls {
+            assert_eq!(("owner", "repo"), gh_repo(url).unwrap());
+        }
+    }
+
+    #[test]
+    fn should_fail_github_url_parsing_when_url_is_invalid() {
+        let gitlab = gh_repo("https://gitlab.com/owner/repo");
+        let nested = gh_repo("https://github.com/owner/repo/tree/main");
+        let no_repo = gh_repo("https://github.com/owner");
+
+        assert_eq!(
+            "Expected GitHub repository URL, found \"https://gitlab.com/owner/repo\"",
+            gitlab.unwrap_err().to_string()
+        );
+        assert!(nested.is_err());
+        assert!(no_repo.is_err());
+    }
+
+    #[test]
+    fn should_look_up_first_and_last_array_elements() {
+        let toml: Value = toml::from_str(r#"list = ["a", "b", "c"]"#).unwrap();
+        let first: TomlIndex = syn::parse_str(r#""list".first"#).unwrap();
+        let last: TomlIndex = syn::parse_str(r#""list".last"#).unwrap();
+
+        assert_eq!("a", lookup(first, &toml).unwrap().as_str().unwrap());
+        assert_eq!("c", lookup(last, &toml).unwrap().as_str().unwrap());
+    }
+
+    #[test]
+    fn should_fail_first_and_last_when_array_is_empty() {
+        let toml: Value = toml::from_str("list = []").unwrap();
+        let first: TomlIndex = syn::parse_str(r#""list".first"#).unwrap();
+        let last: TomlIndex = syn::parse_str(r#""list".last"#).unwrap();
+
+        assert_eq!(
+            "Cannot index empty array with first",
+            lookup(first, &toml).unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Cannot index empty array with last",
+            lookup(last, &toml).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_parsing_unknown_pseudo_index() {
+        let result = syn::parse_str::<TomlIndex>(r#""list".middle"#);
+
+        assert_eq!(
+            "Unknown index `middle`, expected `first` or `last`",
+            result.err().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "include_toml_source")]
+    fn should_render_source_of_last_array_element() {
+        let document: DocumentMut = "list = [1, 2, 3]".parse().unwrap();
+        let index: TomlIndex = syn::parse_str(r#""list".last"#).unwrap();
+
+        let result = source(index, &document, "Cargo.toml").unwrap();
+
+        assert_eq!("3", result);
+    }
+
+    #[test]
+    fn should_convert_integer_table_to_sorted_pairs() {
+        let toml: Value = toml::from_str("[codes]\nok = 0\nnot-found = 404\nerror = 500").unwrap();
+
+        let result = to_int_pairs(toml["codes"].clone()).unwrap();
+
+        assert_eq!(
+            r#"& [("error" , 500i64) , ("not-found" , 404i64) , ("ok" , 0i64)]"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_int_pairs_conversion_when_value_is_not_integer() {
+        let toml: Value = toml::from_str("[codes]\nok = 0\nmoved = \"301\"").unwrap();
+
+        let result = to_int_pairs(toml["codes"].clone());
+
+        assert_eq!(
+            "Key \"moved\": Expected integer, found string",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_format_integer_as_hexadecimal() {
+        assert_eq!("0x1F", hex(Value::Integer(31), 0).unwrap());
+        assert_eq!("0x0", hex(Value::Integer(0), 0).unwrap());
+        assert_eq!("0xCAFE", hex(Value::Integer(0xcafe), 2).unwrap());
+    }
+
+    #[test]
+    fn should_pad_hexadecimal_to_width() {
+        assert_eq!("0x001F", hex(Value::Integer(31), 4).unwrap());
+        assert_eq!("0x00000000", hex(Value::Integer(0), 8).unwrap());
+    }
+
+    #[test]
+    fn should_fail_hexadecimal_when_integer_is_negative() {
+        let result = hex(Value::Integer(-1), 0);
+
+        assert_eq!(
+            "Expected non-negative integer, found -1",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_parse_hex_input_with_optional_width() {
+        let without: HexInput = syn::parse_str(r#""magic""#).unwrap();
+        let with: HexInput = syn::parse_str(r#""magic", 4"#).unwrap();
+
+        assert_eq!(0, without.width);
+        assert_eq!(4, with.width);
+    }
+
+    #[test]
+    fn should_emit_parse_function_for_integer_table() {
+        let toml: Value = toml::from_str("[levels]\ninfo = 1\nerror = 3").unwrap();
+        let name: Ident = syn::parse_str("parse_level").unwrap();
+
+        let result = parse_fn(&name, toml["levels"].clone()).unwrap();
+
+        assert_eq!(
+            "fn parse_level (s : & str) -> :: core :: option :: Option < i64 > { match s { \
+             \"error\" => :: core :: option :: Option :: Some (3i64) , \
+             \"info\" => :: core :: option :: Option :: Some (1i64) , \
+             _ => :: core :: option :: Option :: None , } }",
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_parse_function_when_value_is_not_integer() {
+        let toml: Value = toml::from_str("[levels]\ninfo = \"1\"").unwrap();
+        let name: Ident = syn::parse_str("parse_level").unwrap();
+
+        let result = parse_fn(&name, toml["levels"].clone());
+
+        assert_eq!(
+            "Key \"info\": Expected integer, found string",
+            result.unwrap_err().to_string()
+        );
     }
 
     #[test]
-    fn should_fetch_custom_attribute_when_cargo_toml_is_given() {
+    fn should_apply_transforms_in_order() {
+        let input: IncludeInput = syn::parse_str(r#""name" | unquote | trim | upper"#).unwrap();
+
+        let result = transform(&input.transforms, Value::String(r#"" Name ""#.to_string()));
+
+        assert_eq!("NAME", result.unwrap().as_str().unwrap());
+    }
+
+    #[test]
+    fn should_fail_transform_when_value_is_not_string() {
+        let input: IncludeInput = syn::parse_str(r#""port" | trim"#).unwrap();
+
+        let result = transform(&input.transforms, Value::Integer(8080));
+
+        assert_eq!(
+            "Cannot transform value: Expected string, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_parsing_unknown_transform() {
+        let result = syn::parse_str::<IncludeInput>(r#""name" | reverse"#);
+
+        assert_eq!(
+            "Unknown transform `reverse`, expected `trim`, `upper`, `lower` or `unquote`",
+            result.err().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_value_when_env_matches() {
+        let value = Value::String("0.3.1".to_string());
+
+        let result = assert_env(value, "RELEASE_VERSION", Some("0.3.1".to_string()));
+
+        assert_eq!(quote!("0.3.1").to_string(), result.unwrap().to_string());
+    }
+
+    #[test]
+    fn should_fail_assert_env_when_env_differs() {
+        let value = Value::String("0.3.1".to_string());
+
+        let result = assert_env(value, "RELEASE_VERSION", Some("0.4.0".to_string()));
+
+        assert_eq!(
+            "Value `0.3.1` does not match environment variable `RELEASE_VERSION` with value `0.4.0`",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_assert_env_when_env_is_unset() {
+        let value = Value::String("0.3.1".to_string());
+
+        let result = assert_env(value, "RELEASE_VERSION", None);
+
+        assert_eq!(
+            "Environment variable `RELEASE_VERSION` is not set",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_spread_heterogeneous_array_into_consts() {
+        let input: ConstDefInput = syn::parse_str(r#"ARG, "config"."args""#).unwrap();
+        let toml: Value = toml::from_str(r#"args = ["-v", 3]"#).unwrap();
+
+        let result = spread_consts(&input.attributes(), &input.name, toml["args"].clone()).unwrap();
+
+        assert_eq!(
+            r#"const ARG_0 : & 'static str = "-v" ; const ARG_1 : i64 = 3i64 ;"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_spreading_consts_when_value_is_not_array() {
+        let name: Ident = syn::parse_str("ARG").unwrap();
+
+        let result = spread_consts(&quote!(), &name, Value::Integer(3));
+
+        assert_eq!(
+            "Expected array, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_string_to_cow() {
+        let result = to_cow(Value::String("include-cargo-toml2".to_string())).unwrap();
+
+        assert_eq!(
+            r#":: std :: borrow :: Cow :: < 'static , str > :: Borrowed ("include-cargo-toml2")"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_cow_conversion_when_value_is_not_string() {
+        let result = to_cow(Value::Boolean(true));
+
+        assert_eq!(
+            "Expected string, found boolean",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_select_table_of_array_by_key() {
+        let cargo_toml = r#"
+        [[servers]]
+        id = "main"
+        port = 80
+        [[servers]]
+        id = "backup"
+        port = 8081
+        "#;
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let index: TomlIndex = syn::parse_str(r#""servers"[id = "backup"]."port""#).unwrap();
+
+        let value = lookup(index, &toml).unwrap();
+
+        assert_eq!(8081, value.as_integer().unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "include_toml_source")]
+    fn should_render_source_of_table_of_array_selected_by_key() {
+        let cargo_toml = r#"
+        [[servers]]
+        id = "main"
+        port = 80
+        [[servers]]
+        id = "backup"
+        port = 8081
+        "#;
+        let document: DocumentMut = cargo_toml.parse().unwrap();
+        let index: TomlIndex = syn::parse_str(r#""servers"[id = "backup"]."port""#).unwrap();
+
+        let source = source(index, &document, "Cargo.toml").unwrap();
+
+        assert_eq!("8081", source);
+    }
+
+    #[test]
+    fn should_fail_selecting_table_when_no_or_several_tables_match() {
+        let cargo_toml = r#"
+        [[servers]]
+        id = 1
+        [[servers]]
+        id = 1
+        "#;
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let missing: TomlIndex = syn::parse_str(r#""servers"["id" = 2]["check the ids"]"#).unwrap();
+        let ambiguous: TomlIndex = syn::parse_str(r#""servers"[id = 1]"#).unwrap();
+
+        let missing = lookup(missing, &toml);
+        let ambiguous = lookup(ambiguous, &toml);
+
+        assert_eq!(
+            "Cannot find table with \"id\" = 2 in array\nhelp: check the ids",
+            missing.unwrap_err().to_string()
+        );
+        assert_eq!(
+            r#"Found 2 tables with "id" = 1 in array, expected one"#,
+            ambiguous.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_accept_stable_version() {
+        let toml: Value = toml::from_str("[package]\nversion = \"1.2.3\"").unwrap();
+
+        let result = assert_stable(&toml).unwrap();
+
+        assert_eq!("()", result.to_string());
+    }
+
+    #[test]
+    fn should_fail_assert_stable_when_version_is_pre_release() {
+        let toml: Value = toml::from_str("[package]\nversion = \"1.2.3-alpha\"").unwrap();
+
+        let result = assert_stable(&toml);
+
+        assert_eq!(
+            "Version `1.2.3-alpha` is a pre-release `alpha`, expected stable release",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_assert_stable_when_version_has_build_metadata_or_is_invalid() {
+        let build = stable("1.2.3+build.5");
+        let invalid = stable("1.2");
+
+        assert_eq!(
+            "Version `1.2.3+build.5` has build metadata `build.5`, expected stable release",
+            build.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Cannot parse version `1.2` as semantic version",
+            invalid.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_sum_table_of_integers() {
+        let toml: Value = toml::from_str("[weights]\ncore = 3\nextra = 2\nnegative = -1").unwrap();
+
+        let result = to_sum(toml["weights"].clone()).unwrap();
+
+        assert_eq!("4i64", result.to_string());
+    }
+
+    #[test]
+    fn should_fail_sum_when_value_is_not_integer_or_sum_overflows() {
+        let toml: Value =
+            toml::from_str("[mixed]\na = 1\nb = \"2\"\n[large]\na = 9223372036854775807\nb = 1")
+                .unwrap();
+
+        let mixed = to_sum(toml["mixed"].clone());
+        let large = to_sum(toml["large"].clone());
+
+        assert_eq!(
+            "Key \"b\": Expected integer, found string",
+            mixed.unwrap_err().to_string()
+        );
+        assert_eq!("Sum of table overflows i64", large.unwrap_err().to_string());
+    }
+
+    #[test]
+    fn should_emit_static_value_through_typed_constant() {
+        let toml: Value = toml::from_str(r#"args = ["-v", ["nested"]]"#).unwrap();
+
+        let result = to_static(toml["args"].clone()).unwrap();
+
+        assert_eq!(
+            r#"{ const VALUE : (& 'static str , (& 'static str ,) ,) = ("-v" , ("nested" ,) ,) ; VALUE }"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_decode_base64_with_and_without_padding() {
+        let padded = to_base64_bytes(Value::String("aGVsbG8=".to_string())).unwrap();
+        let unpadded = base64("aGk").unwrap();
+
+        assert_eq!(
+            "[104u8 , 101u8 , 108u8 , 108u8 , 111u8]",
+            padded.to_string()
+        );
+        assert_eq!(b"hi".to_vec(), unpadded);
+    }
+
+    #[test]
+    fn should_fail_base64_decoding_when_input_is_invalid() {
+        let character = base64("aGV*bG8=");
+        let length = base64("aGVsb");
+        let not_string = to_base64_bytes(Value::Integer(1));
+
+        assert_eq!(
+            "Invalid base64 character '*' at position 3",
+            character.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Invalid length of base64 string \"aGVsb\"",
+            length.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Expected string, found integer",
+            not_string.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_extract_email_of_author() {
         let cargo_toml = r#"
         [package]
-        edition="2021"
+        authors = ["Jane Doe", "John Doe <john@example.com>"]
+        "#;
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let index: LitInt = syn::parse_str("1").unwrap();
+
+        let result = author_email(&toml, &index).unwrap();
+
+        assert_eq!(quote!("john@example.com").to_string(), result.to_string());
+    }
+
+    #[test]
+    fn should_fail_extracting_email_when_author_has_none_or_invalid() {
+        let cargo_toml = r#"
+        [package]
+        authors = ["Jane Doe"]
+        "#;
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+        let index: LitInt = syn::parse_str("0").unwrap();
+
+        let missing = author_email(&toml, &index);
+        let invalid = email("John Doe <john@localhost>");
+
+        assert_eq!(
+            "Author \"Jane Doe\" has no email address",
+            missing.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Invalid email address \"john@localhost\" of author \"John Doe <john@localhost>\"",
+            invalid.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_convert_percentage_to_fraction() {
+        let half = to_percent(Value::String("50%".to_string())).unwrap();
+        let full = to_percent(Value::String("100%".to_string())).unwrap();
+
+        assert_eq!("0.5f64", half.to_string());
+        assert_eq!("1f64", full.to_string());
+    }
+
+    #[test]
+    fn should_fail_percent_conversion_when_string_is_invalid() {
+        let missing = to_percent(Value::String("50".to_string()));
+        let invalid = to_percent(Value::String("half%".to_string()));
+
+        assert_eq!(
+            "Expected percentage like \"75%\", found \"50\"",
+            missing.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Expected percentage like \"75%\", found \"half%\"",
+            invalid.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_select_value_of_deeply_nested_tables() {
+        let keys: Vec<String> = (0..12).map(|level| format!("level{}", level)).collect();
+        let cargo_toml = format!("[{}]\nvalue = [1, {{ leaf = \"deep\" }}]", keys.join("."));
+        let toml: Value = toml::from_str(&cargo_toml).expect("Cannot parse Cargo.toml");
+        let path = keys
+            .iter()
+            .map(|key| format!("{:?}", key))
+            .collect::<Vec<_>>()
+            .join(".");
+        let index: TomlIndex = syn::parse_str(&format!(r#"{}."value".last."leaf""#, path)).unwrap();
+        let table: TomlIndex = syn::parse_str(&path).unwrap();
+
+        let leaf = lookup(index, &toml).unwrap();
+        let nested = translate(lookup(table, &toml).unwrap()).unwrap();
+
+        assert_eq!("deep", leaf.as_str().unwrap());
+        assert_eq!(
+            r#"(("value" , (1i64 , (("leaf" , "deep") ,) ,)) ,)"#,
+            nested.to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_documented_const_item() {
+        let input: ConstDefInput = syn::parse_str(r#"NAME, "package"."name""#).unwrap();
+
+        let result = documented_const(
+            &input.name,
+            &dotted(&input.index),
+            Value::String("a".to_string()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            r#"# [doc = " Value of package.name from Cargo.toml"] pub const NAME : & 'static str = "a" ;"#,
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_reverse_array_as_tuple_or_array() {
+        let toml: Value = toml::from_str("list = [1, 2, 3]").unwrap();
+        let arrays = Options {
+            arrays: true,
+            ..Options::default()
+        };
+
+        let tuple = rev(toml["list"].clone(), &Options::default()).unwrap();
+        let array = rev(toml["list"].clone(), &arrays).unwrap();
+
+        assert_eq!("(3i64 , 2i64 , 1i64 ,)", tuple.to_string());
+        assert_eq!("[3i64 , 2i64 , 1i64 ,]", array.to_string());
+    }
+
+    #[test]
+    fn should_fail_reversing_when_value_is_not_array() {
+        let result = rev(Value::String("a".to_string()), &Options::default());
+
+        assert_eq!(
+            "Expected array, found string",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_parse_allow_unused_modifier_of_const_def() {
+        let plain: ConstDefInput = syn::parse_str(r#"NAME, "package"."name""#).unwrap();
+        let allowed: ConstDefInput =
+            syn::parse_str(r#"allow_unused NAME, "package"."name""#).unwrap();
+        let unknown = syn::parse_str::<ConstDefInput>(r#"allow NAME, "package"."name""#);
+
+        assert_eq!("", plain.attributes().to_string());
+        assert_eq!("# [allow (dead_code)]", allowed.attributes().to_string());
+        assert_eq!("NAME", allowed.name.to_string());
+        assert_eq!(
+            "Unknown modifier `allow`, expected `allow_unused`",
+            unknown.err().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn should_compare_versions() {
+        let greater = version_ge("1.75.0", "1.70");
+        let equal = version_ge("1.70", "1.70.0");
+        let lesser = version_ge("1.65", "1.70");
+        let pre_release = version_ge("1.70.0-beta.1", "1.70");
+
+        assert!(greater.unwrap());
+        assert!(equal.unwrap());
+        assert!(!lesser.unwrap());
+        assert!(!pre_release.unwrap());
+    }
+
+    #[test]
+    fn should_fail_comparing_versions_when_version_is_invalid() {
+        let result = version_ge("1.x", "1.70");
+
+        assert_eq!(
+            "Cannot parse version `1.x` as semantic version",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_format_values_into_string() {
+        let input: FmtInput =
+            syn::parse_str(r#""{{{}}} v{}", "package"."name", "package"."version","#).unwrap();
+        let values = ["app".to_string(), "1.0.0".to_string()];
+
+        let result = fmt(&input.format.value(), &values).unwrap();
+
+        assert_eq!(2, input.indexes.len());
+        assert_eq!("{app} v1.0.0", result);
+    }
+
+    #[test]
+    fn should_fail_formatting_when_arity_mismatches() {
+        let too_few = fmt("{} {}", &["a".to_string()]);
+        let too_many = fmt("{}", &["a".to_string(), "b".to_string()]);
+
+        assert_eq!(
+            "Format string has 2 placeholders, but 1 values are given",
+            too_few.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Format string has 1 placeholders, but 2 values are given",
+            too_many.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_formatting_when_brace_is_unmatched() {
+        let result = fmt("{name}", &[]);
+
+        assert_eq!(
+            "Invalid format string \"{name}\", expected `{}` or escaped braces",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_body_only_when_boolean_is_true() {
+        let input: WhenInput = syn::parse_str(r#""features"."extra", { fn extra() {} }"#).unwrap();
+
+        let enabled = if_true(Value::Boolean(true), input.body.clone()).unwrap();
+        let disabled = if_true(Value::Boolean(false), input.body.clone()).unwrap();
+        let invalid = if_true(Value::Integer(1), input.body);
+
+        assert_eq!("fn extra () { }", enabled.to_string());
+        assert!(disabled.is_empty());
+        assert_eq!(
+            "Expected boolean, found integer",
+            invalid.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_compute_stats_of_nested_table() {
+        let cargo_toml = r#"
+        [package]
+        name = "a"
+        keywords = ["b", "c"]
         [package.metadata.deb]
-        revision=4
+        revision = 4
+        assets = [["src", "dst"]]
         "#;
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
 
-        let toml: Value = cargo_toml.parse::<Value>().expect("Cannot parse Cargo.toml");
-        let index = syn::parse_str(r#""package"."metadata"."deb"."revision""#).unwrap();
+        let result = to_stats(toml["package"].clone()).unwrap();
 
-        let result = lookup(index, toml);
+        assert_eq!("(3usize , 6usize , 5usize)", result.to_string());
+    }
 
-        assert_eq!(4, result.as_integer().unwrap());
+    #[test]
+    fn should_fail_stats_when_value_is_scalar() {
+        let result = to_stats(Value::Integer(1));
+
+        assert_eq!(
+            "Expected table or array, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_rotate_letters_by_13() {
+        let result = rot13("Hello, World! ä");
+
+        assert_eq!("Uryyb, Jbeyq! ä", result);
+        assert_eq!("Hello, World! ä", rot13(&result));
+    }
+
+    #[test]
+    fn should_fail_rot13_when_value_is_not_string() {
+        let result = to_rot13(Value::Integer(13));
+
+        assert_eq!(
+            "Expected string, found integer",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_compute_min_max_and_sum_of_integer_array() {
+        let toml: Value = toml::from_str("samples = [3, -1, 7]").unwrap();
+
+        let result = to_array_stats(toml["samples"].clone()).unwrap();
+
+        assert_eq!("(- 1i64 , 7i64 , 9i64)", result.to_string());
+    }
+
+    #[test]
+    fn should_fail_array_stats_when_array_is_empty_or_not_integer() {
+        let toml: Value = toml::from_str("empty = []\nmixed = [1, 2.5]").unwrap();
+
+        let empty = to_array_stats(toml["empty"].clone());
+        let mixed = to_array_stats(toml["mixed"].clone());
+
+        assert_eq!(
+            "Cannot compute statistics of empty array",
+            empty.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Element 1: Expected integer, found float",
+            mixed.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_default_impl_with_fields_and_base() {
+        let input: DefaultImplInput =
+            syn::parse_str(r#"config::Config { port: "server"."port", ..Config::BASE }"#).unwrap();
+
+        let result = default_impl(&input.ty, &[quote!(port: 8080)], input.base.as_ref());
+
+        assert_eq!(1, input.fields.len());
+        assert_eq!(
+            "impl :: core :: default :: Default for config :: Config { fn default () -> Self { Self { port : 8080 , .. Config :: BASE } } }",
+            result.to_string()
+        );
+    }
+
+    #[test]
+    fn should_emit_single_token_tree_for_every_type() {
+        let cargo_toml = r#"
+        name = "a"
+        count = 5
+        negative = -5
+        flag = true
+        list = [1, "b"]
+        "#;
+        let toml: Value = toml::from_str(cargo_toml).expect("Cannot parse Cargo.toml");
+
+        let trees = ["name", "count", "negative", "flag", "list"]
+            .iter()
+            .map(|key| token_tree(toml[*key].clone()).unwrap())
+            .collect::<Vec<_>>();
+
+        for tree in &trees {
+            assert_eq!(1, tree.clone().into_iter().count());
+        }
+        assert_eq!("(- 5i64)", trees[2].to_string());
+        assert_eq!("(1i64 , \"b\" ,)", trees[4].to_string());
+    }
+
+    #[test]
+    fn should_parse_callback_of_tokens_input() {
+        let input: TokensInput = syn::parse_str(r#""package"."name", crate::shout"#).unwrap();
+
+        let callback = input.callback.unwrap();
+
+        assert_eq!("crate :: shout", callback.to_token_stream().to_string());
+    }
+
+    #[test]
+    fn should_fail_float_conversion_when_value_is_not_finite() {
+        let f32_type: Ident = syn::parse_str("f32").unwrap();
+        let f64_type: Ident = syn::parse_str("f64").unwrap();
+
+        let big = to_type(Value::Float(1e300), &f32_type);
+        let infinite = to_type(Value::Float(f64::INFINITY), &f64_type);
+        let nan = translate(Value::Float(f64::NAN));
+
+        assert_eq!(
+            "Value 1e300 is out of range for f32",
+            big.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Value inf is out of range for f64",
+            infinite.unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Value NaN is out of range for f64",
+            nan.unwrap_err().to_string()
+        );
+        assert!(to_type(Value::Float(1e300), &f64_type).is_ok());
+    }
+
+    #[test]
+    fn should_parse_optional_source_modifier() {
+        let with_source: Sourced<ParseFnInput> =
+            syn::parse_str(r#"first ["a.toml", "b.toml"], parse, "levels""#).unwrap();
+        let named_first: Sourced<ParseFnInput> = syn::parse_str(r#"first, "levels""#).unwrap();
+
+        match with_source.source {
+            Source::First(paths) => assert_eq!(vec!["a.toml", "b.toml"], paths),
+            _ => panic!("Expected first source"),
+        }
+        assert_eq!("parse", with_source.input.name.to_string());
+        assert!(matches!(named_first.source, Source::CargoToml));
+        assert_eq!("first", named_first.input.name.to_string());
+    }
+
+    #[test]
+    fn should_treat_only_missing_key_as_empty() {
+        let toml: Value = toml::from_str("[package]\nname = \"a\"").unwrap();
+        let missing = syn::parse_str(r#""package"."metadata"."deb""#).unwrap();
+        let scalar = syn::parse_str(r#""package"."name"."first""#).unwrap();
+
+        assert_eq!(None, lookup_opt(missing, &toml, "Cargo.toml").unwrap());
+        assert_eq!(
+            "Cannot index string with key \"first\"",
+            lookup_opt(scalar, &toml, "Cargo.toml")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn should_name_file_in_error_when_key_is_missing() {
+        let toml: Value = toml::from_str("[server]\nport = 8080").unwrap();
+        let index = syn::parse_str(r#""server"."host""#).unwrap();
+
+        let result = lookup_in(index, &toml, "config.toml");
+
+        assert_eq!(
+            "Cannot find key \"host\" in config.toml",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "include_toml_source")]
+    fn should_name_file_in_error_when_key_of_source_is_missing() {
+        let document: DocumentMut = "[server]\nport = 8080".parse().unwrap();
+        let index = syn::parse_str(r#""server"."host""#).unwrap();
+
+        let result = source(index, &document, "config.toml");
+
+        assert_eq!(
+            "Cannot find key \"host\" in config.toml",
+            result.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_fail_json_pointer_when_array_index_has_leading_zeros() {
+        let toml = document(toml::from_str(POINTERS).unwrap());
+        let follow_err = |pointer: &str| {
+            let pointer = LitStr::new(pointer, Span2::call_site());
+            follow(&pointer, &toml).unwrap_err().to_string()
+        };
+
+        assert_eq!(
+            "Array index \"01\" in JSON Pointer has leading zeros",
+            follow_err("/10/list/01")
+        );
+        assert_eq!(
+            "Cannot index array with key \"+1\"",
+            follow_err("/10/list/+1")
+        );
+    }
+
+    #[test]
+    fn should_uppercase_raw_identifier_as_constant_name() {
+        let raw: Ident = syn::parse_str("r#type").unwrap();
+        let plain: Ident = syn::parse_str("version").unwrap();
+
+        assert_eq!("TYPE", const_name(&raw).unwrap().to_string());
+        assert_eq!("VERSION", const_name(&plain).unwrap().to_string());
     }
 }
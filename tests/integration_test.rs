@@ -1,5 +1,7 @@
 #![cfg(test)]
 
+use include_cargo_toml2::include_toml;
+
 mod submodule;
 
 /// Tests whether the macro is independent of the folder structure.
@@ -8,3 +10,374 @@ mod submodule;
 pub fn load_version_from_inner_folder() {
     assert_eq!(submodule::CRATE_NAME, "include-cargo-toml2");
 }
+
+/// Tests whether the first readable file is used.
+#[test]
+pub fn load_from_first_existing_file() {
+    assert_eq!(
+        include_toml!(first ["tests/fixtures/config.local.toml", "tests/fixtures/config.toml"], "server"."port"),
+        3000
+    );
+    assert_eq!(
+        include_toml!(first ["tests/fixtures/missing.toml", "tests/fixtures/config.toml"], "server"."port"),
+        8080
+    );
+}
+
+/// Tests whether the index is selected by the `cfg` predicate.
+#[test]
+pub fn load_conditionally_on_cfg_predicate() {
+    let name = include_toml!(cfg(test) ? "package"."name" : "package"."version");
+    let version = include_toml!(cfg(not(test)) ? "package"."name" : "package"."version");
+
+    assert_eq!(name, "include-cargo-toml2");
+    assert_eq!(version, "0.3.1");
+}
+
+/// Tests whether negative integers are emitted as valid tokens.
+#[test]
+pub fn load_negative_integer() {
+    assert_eq!(include_toml!(first ["tests/fixtures/config.toml"], "numbers"."negative"), -5i64);
+    assert_eq!(include_toml!(raw first ["tests/fixtures/config.toml"], "numbers"."negative") + 5, 0);
+}
+
+/// Tests whether profile settings of different types can be loaded.
+#[test]
+pub fn load_opt_level_of_profiles() {
+    assert_eq!(include_toml!(first ["tests/fixtures/profile.toml"], "profile"."dev"."opt-level"), 1);
+    assert_eq!(include_toml!(first ["tests/fixtures/profile.toml"], "profile"."release"."opt-level"), "s");
+}
+
+/// Tests whether values can be converted to custom types.
+#[test]
+pub fn load_value_into_custom_type() {
+    use include_cargo_toml2::include_toml_into;
+
+    #[derive(Debug, PartialEq)]
+    enum CrateType {
+        Lib,
+        ProcMacro,
+    }
+
+    impl From<bool> for CrateType {
+        fn from(proc_macro: bool) -> Self {
+            if proc_macro {
+                CrateType::ProcMacro
+            } else {
+                CrateType::Lib
+            }
+        }
+    }
+
+    assert_eq!(include_toml_into!(CrateType, "lib"."proc-macro"), CrateType::ProcMacro);
+    assert_ne!(include_toml_into!(CrateType, "lib"."proc-macro"), CrateType::Lib);
+}
+
+/// Tests whether unsuffixed integers can be used as const generic arguments.
+#[test]
+pub fn load_integer_as_const_generic_argument() {
+    struct Port<const N: u16>;
+
+    impl<const N: u16> Port<N> {
+        fn number(&self) -> u16 {
+            N
+        }
+    }
+
+    let port = Port::<{ include_toml!(raw first ["tests/fixtures/config.toml"], "server"."port") }>;
+    assert_eq!(port.number(), 8080);
+}
+
+/// Tests whether later files override and extend earlier ones.
+#[test]
+pub fn load_from_merged_files() {
+    use include_cargo_toml2::include_toml_merged;
+
+    assert_eq!(
+        include_toml_merged!(["tests/fixtures/config.toml", "tests/fixtures/override.toml"], "server"."port"),
+        9090
+    );
+    assert_eq!(
+        include_toml_merged!(["tests/fixtures/config.toml", "tests/fixtures/override.toml"], "server"."host"),
+        "localhost"
+    );
+    assert_eq!(
+        include_toml_merged!(["tests/fixtures/config.toml", "tests/fixtures/override.toml"], "numbers"."negative"),
+        -5
+    );
+}
+
+/// Tests whether tables are translated to tuples of key-value pairs.
+#[test]
+pub fn load_table_as_tuple_of_pairs() {
+    assert_eq!(include_toml!("lib"), (("proc-macro", true),));
+    assert_eq!(
+        include_toml!(first ["tests/fixtures/override.toml"], "server"),
+        (("host", "localhost"), ("port", 9090))
+    );
+    assert_eq!(
+        include_toml!(first ["tests/fixtures/config.toml"], "numbers"."mixed"),
+        (1, (("a", 2), ("b", "c")))
+    );
+}
+
+/// Tests whether the accessor constructs the value only once.
+#[test]
+pub fn load_value_once() {
+    use include_cargo_toml2::include_toml_once;
+
+    let mixed = include_toml_once!("package"."keywords");
+    let first = mixed();
+    let second = mixed();
+
+    assert_eq!(first.2, "Cargo-toml");
+    assert!(std::ptr::eq(first, second));
+}
+
+/// Tests whether a local macro can prepend a base path to the index.
+#[test]
+pub fn load_relative_to_base() {
+    macro_rules! server {
+        ($($index:tt).+) => {
+            include_toml!(first ["tests/fixtures/config.toml"], "server".$($index).+)
+        };
+    }
+
+    assert_eq!(server!("port"), 8080);
+}
+
+/// Tests whether the include and exclude globs of a package are loaded.
+#[test]
+pub fn load_include_and_exclude() {
+    assert_eq!(
+        include_toml!(first ["tests/fixtures/package.toml"], "package"."include"),
+        ("src/**/*.rs", "Cargo.toml")
+    );
+    assert_eq!(
+        include_toml!(first ["tests/fixtures/package.toml"], "package"."exclude"),
+        ()
+    );
+}
+
+/// Tests whether booleans can be used as const generic arguments.
+#[test]
+pub fn load_bool_as_const_generic() {
+    use include_cargo_toml2::include_toml_bool_const;
+
+    struct Switch<const ON: bool>;
+
+    impl<const ON: bool> Switch<ON> {
+        fn on(&self) -> bool {
+            ON
+        }
+    }
+
+    assert!(Switch::<{ include_toml!("lib"."proc-macro") }>.on());
+    assert!(Switch::<{ include_toml_bool_const!("lib"."proc-macro") }>.on());
+}
+
+mod constants {
+    use include_cargo_toml2::include_toml_const_def;
+
+    include_toml_const_def!(NAME, "package"."name");
+    include_toml_const_def!(KEYWORDS, "package"."keywords");
+    include_cargo_toml2::include_toml_documented_const!(DESCRIPTION, "package"."description");
+}
+
+include_cargo_toml2::include_toml_spread_consts!(KEYWORD, "package"."keywords");
+
+#[deny(dead_code)]
+mod unused {
+    include_cargo_toml2::include_toml_const_def!(allow_unused NAME, "package"."name");
+    include_cargo_toml2::include_toml_spread_consts!(allow_unused KEYWORD, "package"."keywords");
+}
+
+include_cargo_toml2::include_toml_mod!(manifest, {
+    name: "package"."name",
+    version: "package"."version",
+    r#type: "package"."edition",
+});
+
+/// Tests whether generated constants can be referenced from other modules.
+#[test]
+pub fn load_constant_definitions() {
+    assert_eq!(constants::NAME, "include-cargo-toml2");
+    assert_eq!(constants::KEYWORDS.4, "parse");
+    assert_eq!(
+        constants::DESCRIPTION,
+        "Load properties of Cargo.toml at compile time"
+    );
+}
+
+/// Tests whether constants are generated inside a module.
+#[test]
+pub fn load_module_of_constants() {
+    assert_eq!(manifest::NAME, "include-cargo-toml2");
+    assert_eq!(manifest::VERSION, "0.3.1");
+    assert_eq!(manifest::TYPE, "2018");
+}
+
+/// Tests whether array elements are generated as separate constants.
+#[test]
+pub fn load_spread_constants() {
+    assert_eq!(KEYWORD_0, "macro");
+    assert_eq!(KEYWORD_1, "version");
+}
+
+/// Tests whether tables in an array are selected by key.
+#[test]
+pub fn load_table_of_array_by_key() {
+    assert_eq!(
+        include_toml!(first ["tests/fixtures/config.toml"], "servers"[id = "main"]."port"),
+        80
+    );
+    assert_eq!(
+        include_toml!(first ["tests/fixtures/config.toml"], "servers"[id = "backup"]."port"),
+        8081
+    );
+}
+
+static PACKAGE: (&str, (&str, &str, &str, &str, &str)) = (
+    include_cargo_toml2::include_toml_static!("package"."name"),
+    include_cargo_toml2::include_toml_static!("package"."keywords"),
+);
+
+/// Tests whether nested values can be stored in a static item.
+#[test]
+pub fn load_value_into_static() {
+    assert_eq!(PACKAGE.0, "include-cargo-toml2");
+    assert_eq!((PACKAGE.1).4, "parse");
+}
+
+/// Tests whether obfuscated strings are restored by the generated decoder.
+#[test]
+pub fn load_rot13_string() {
+    let (hidden, decode) = include_cargo_toml2::include_toml_rot13!("package"."description");
+    assert_ne!(hidden, "Load properties of Cargo.toml at compile time");
+    assert_eq!(decode(hidden), "Load properties of Cargo.toml at compile time");
+}
+
+#[derive(Debug, PartialEq)]
+struct Library {
+    name: &'static str,
+    proc_macro: bool,
+}
+
+include_cargo_toml2::include_toml_default_impl!(Library {
+    name: "package"."name",
+    proc_macro: "lib"."proc-macro",
+});
+
+/// Tests whether a Default implementation is seeded from Cargo.toml.
+#[test]
+pub fn load_default_impl() {
+    assert_eq!(
+        Library::default(),
+        Library {
+            name: "include-cargo-toml2",
+            proc_macro: true,
+        }
+    );
+}
+
+macro_rules! token_kind {
+    ($value:literal) => {
+        "literal"
+    };
+    ($value:tt) => {
+        "tree"
+    };
+}
+
+/// Tests whether values are passed to macros as single token trees.
+#[test]
+pub fn load_value_as_token_tree() {
+    use include_cargo_toml2::include_toml_tokens;
+
+    assert_eq!(include_toml_tokens!("package"."name", token_kind), "literal");
+    assert_eq!(include_toml_tokens!("lib"."proc-macro", token_kind), "literal");
+    assert_eq!(include_toml_tokens!("package"."keywords", token_kind), "tree");
+}
+
+include_cargo_toml2::include_toml_parse_fn!(first ["tests/fixtures/tables.toml"], parse_level, "levels");
+
+/// Tests whether the generated function looks up the integers of a table.
+#[test]
+pub fn load_parse_function() {
+    assert_eq!(parse_level("info"), Some(1));
+    assert_eq!(parse_level("error"), Some(3));
+    assert_eq!(parse_level("trace"), None);
+}
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    name: &'static str,
+    path: &'static str,
+}
+
+/// Tests whether each table of an array is expanded into a struct.
+#[test]
+pub fn load_structs() {
+    const BINARIES: &[Entry] = include_cargo_toml2::include_toml_structs!(
+        first ["tests/fixtures/tables.toml"],
+        Entry,
+        "bin",
+        { name: "name", path: "path" }
+    );
+
+    assert_eq!(
+        BINARIES,
+        [
+            Entry {
+                name: "server",
+                path: "src/server.rs",
+            },
+            Entry {
+                name: "mytool",
+                path: "src/mytool.rs",
+            },
+        ]
+    );
+}
+
+mod commands {
+    pub fn build() -> &'static str {
+        "building"
+    }
+
+    pub fn test() -> &'static str {
+        "testing"
+    }
+}
+
+/// Calls the handler of command `name` from the dispatch table.
+fn run(name: &str) -> Option<&'static str> {
+    let handler: Option<fn() -> &'static str> = include_cargo_toml2::include_toml_match!(
+        first ["tests/fixtures/tables.toml"],
+        name,
+        "commands",
+        "name",
+        "handler"
+    );
+    handler.map(|handler| handler())
+}
+
+/// Tests whether the generated match dispatches to the handlers.
+#[test]
+pub fn load_dispatch_table() {
+    assert_eq!(run("build"), Some("building"));
+    assert_eq!(run("test"), Some("testing"));
+    assert_eq!(run("deploy"), None);
+}
+
+/// Tests whether the array and tuple modifiers select the representation of arrays.
+#[test]
+pub fn load_array_with_array_and_tuple_modifiers() {
+    let array: [&str; 5] = include_toml!(array "package"."keywords");
+    let tuple: (&str, &str, &str, &str, &str) = include_toml!(tuple "package"."keywords");
+    let default: (&str, &str, &str, &str, &str) = include_toml!("package"."keywords");
+
+    assert_eq!(array, ["macro", "version", "Cargo-toml", "compile-time", "parse"]);
+    assert_eq!(tuple, default);
+    assert_eq!(tuple.4, array[4]);
+}
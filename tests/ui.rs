@@ -0,0 +1,9 @@
+#![cfg(test)]
+
+/// Tests whether errors point at the right part of the macro invocation.
+/// Run with `TRYBUILD=overwrite` to update the expected output in `tests/ui`.
+#[test]
+pub fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,5 @@
+use include_cargo_toml2::include_toml;
+
+fn main() {
+    let _ = include_toml!();
+}
@@ -0,0 +1,5 @@
+use include_cargo_toml2::include_toml_expect;
+
+fn main() {
+    let _ = include_toml_expect!("package"."versio", "version must be set");
+}
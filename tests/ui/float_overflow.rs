@@ -0,0 +1,6 @@
+use include_cargo_toml2::include_toml_as;
+
+// Paths are relative to the project generated by trybuild in `target/tests/trybuild`.
+fn main() {
+    let _ = include_toml_as!(first ["../../../../tests/fixtures/config.toml"], "floats"."big", f32);
+}
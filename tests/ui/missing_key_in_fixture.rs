@@ -0,0 +1,6 @@
+use include_cargo_toml2::include_toml;
+
+// Paths are relative to the project generated by trybuild in `target/tests/trybuild`.
+fn main() {
+    let _ = include_toml!(first ["../../../../tests/fixtures/config.toml"], "server"."host");
+}
@@ -0,0 +1,5 @@
+use include_cargo_toml2::include_toml_strs;
+
+fn main() {
+    let _: &[&str] = include_toml_strs!("package"."exclude");
+}
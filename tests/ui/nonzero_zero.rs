@@ -0,0 +1,10 @@
+use include_cargo_toml2::include_toml_nonzero_u32;
+use std::num::NonZeroU32;
+
+// Paths are relative to the project generated by trybuild in `target/tests/trybuild`.
+const RETRIES: NonZeroU32 = include_toml_nonzero_u32!(
+    first ["../../../../tests/fixtures/metadata.toml"],
+    "package"."metadata"."retries"
+);
+
+fn main() {}
@@ -0,0 +1,5 @@
+use include_cargo_toml2::include_toml_opt;
+
+fn main() {
+    let _: Option<&str> = include_toml_opt!("package"."name"."typo");
+}
@@ -0,0 +1,5 @@
+use include_cargo_toml2::include_toml_static_assert_type;
+
+fn main() {
+    let _ = include_toml_static_assert_type!("package"."name", i64);
+}
@@ -0,0 +1,7 @@
+use include_cargo_toml2::include_toml_when;
+
+include_toml_when!("package"."name"."typo", {
+    fn never() {}
+});
+
+fn main() {}